@@ -0,0 +1,106 @@
+//! Events per second of the owned and borrowed parsing paths over a generated hourly file.
+//!
+//! Run with `cargo bench --bench parse`, add `--features simd` to parse both paths with
+//! simd-json. `BENCH_EVENTS` sets the number of events (default 200000).
+
+use etl_rust::extract::event_ref::for_each_event_ref;
+use etl_rust::extract::filters::EventFilter;
+use etl_rust::extract::json_lines::{ParsedLine, for_each_line};
+use etl_rust::model::github::GitHubEvent;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 3;
+
+fn event(id: usize) -> String {
+    if id.is_multiple_of(3) {
+        format!(
+            r#"{{"id":"{id}","type":"IssuesEvent","actor":{{"id":{id},"login":"user{id}","gravatar_id":"","url":"https://api.github.com/users/user{id}","avatar_url":"https://avatars.githubusercontent.com/u/{id}?"}},"repo":{{"id":7,"name":"owner/repo","url":"https://api.github.com/repos/owner/repo"}},"payload":{{"action":"opened","issue":{{"url":"https://api.github.com/repos/owner/repo/issues/{id}","id":{id},"number":{id},"title":"Crash on \"start\"","body":"Steps:\n1. run\n2. crash\n\nExpected: no crash","state":"open","labels":[{{"name":"bug"}}]}}}},"public":true,"created_at":"2024-01-01T00:00:00Z","org":{{"id":9,"login":"owner","gravatar_id":"","url":"https://api.github.com/orgs/owner","avatar_url":"https://avatars.githubusercontent.com/u/9?"}}}}"#
+        )
+    } else {
+        format!(
+            r#"{{"id":"{id}","type":"PushEvent","actor":{{"id":{id},"login":"user{id}","gravatar_id":"","url":"https://api.github.com/users/user{id}","avatar_url":"https://avatars.githubusercontent.com/u/{id}?"}},"repo":{{"id":7,"name":"owner/repo","url":"https://api.github.com/repos/owner/repo"}},"payload":{{"push_id":{id},"size":1,"distinct_size":1,"ref":"refs/heads/main","head":"6f1c2a9e0b7d4c3a8e5f6a7b8c9d0e1f2a3b4c5d","before":"0e1f2a3b4c5d6f1c2a9e0b7d4c3a8e5f6a7b8c9d","commits":[{{"sha":"6f1c2a9e0b7d4c3a8e5f6a7b8c9d0e1f2a3b4c5d","author":{{"email":"dev@example.com","name":"Dev"}},"message":"Fix the thing","distinct":true,"url":"https://api.github.com/repos/owner/repo/commits/6f1c2a9e"}}]}},"public":true,"created_at":"2024-01-01T00:00:00Z"}}"#
+        )
+    }
+}
+
+/// Best of [`RUNS`] runs of `parse`, which returns the number of events it parsed.
+fn measure(name: &str, events: usize, parse: impl Fn() -> usize) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        assert_eq!(black_box(parse()), events);
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{:<44} {:>12.0} events/s ({:.2?})",
+        name,
+        events as f64 / best.as_secs_f64(),
+        best
+    );
+}
+
+fn main() {
+    let events: usize = std::env::var("BENCH_EVENTS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(200_000);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("2024-01-01-0.json");
+    let lines: Vec<String> = (0..events).map(event).collect();
+    fs::write(&path, lines.join("\n")).unwrap();
+    drop(lines);
+    let path: &Path = &path;
+
+    println!(
+        "{} events, {} MB",
+        events,
+        fs::metadata(path).unwrap().len() / 1_000_000
+    );
+
+    measure("owned, one String per line", events, || {
+        BufReader::new(File::open(path).unwrap())
+            .lines()
+            .filter(|line| {
+                let line = line.as_ref().unwrap();
+                serde_json::from_str::<GitHubEvent>(line).is_ok()
+            })
+            .count()
+    });
+
+    measure("owned, parallel batches (for_each_line)", events, || {
+        let mut count = 0;
+        for_each_line(path, &EventFilter::default(), |parsed| {
+            if let ParsedLine::Event(event) = parsed {
+                black_box(event);
+                count += 1;
+            }
+            Ok(())
+        })
+        .unwrap();
+        count
+    });
+
+    let backend = if cfg!(feature = "simd") {
+        "simd-json"
+    } else {
+        "serde_json"
+    };
+    measure(
+        &format!("borrowed, reused buffer ({})", backend),
+        events,
+        || {
+            let mut count = 0;
+            for_each_event_ref(path, |event| {
+                black_box(event?);
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+            count
+        },
+    );
+}
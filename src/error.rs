@@ -0,0 +1,155 @@
+use crate::{EXIT_DATA_ERROR, EXIT_FATAL, EXIT_IO_ERROR};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+type BoxedSource = Box<dyn Error + Send + Sync>;
+
+/// Everything that can go wrong in the library. Messages are complete on their own,
+/// `source()` still exposes the underlying error for callers that want to inspect it.
+#[derive(Debug)]
+pub enum EtlError {
+    /// Reading or writing `path` failed; `action` says what was attempted.
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A line of `file` is not a valid event.
+    Parse {
+        file: PathBuf,
+        line: usize,
+        column: usize,
+        source: serde_json::Error,
+    },
+    /// An event type, name pattern, time bound or `--where` expression was rejected.
+    InvalidFilter {
+        message: String,
+        source: Option<BoxedSource>,
+    },
+    /// An output could not accept events for a reason other than plain I/O.
+    Sink {
+        message: String,
+        source: Option<BoxedSource>,
+    },
+    /// Inconsistent or out-of-range options.
+    Config(String),
+    /// Reading stopped because the error budget was exceeded in fail-fast mode.
+    ErrorBudgetExceeded { limits: String },
+}
+
+impl EtlError {
+    pub fn io(action: &'static str, path: impl AsRef<Path>, source: io::Error) -> Self {
+        EtlError::Io {
+            action,
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn parse(file: impl AsRef<Path>, line: usize, source: serde_json::Error) -> Self {
+        EtlError::Parse {
+            file: file.as_ref().to_path_buf(),
+            line,
+            column: source.column(),
+            source,
+        }
+    }
+
+    pub fn invalid_filter(message: impl Into<String>) -> Self {
+        EtlError::InvalidFilter {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn invalid_filter_caused_by(
+        message: impl Into<String>,
+        source: impl Into<BoxedSource>,
+    ) -> Self {
+        EtlError::InvalidFilter {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    pub fn sink(message: impl Into<String>) -> Self {
+        EtlError::Sink {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn sink_caused_by(message: impl Into<String>, source: impl Into<BoxedSource>) -> Self {
+        EtlError::Sink {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Process exit code for a run that failed with this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            EtlError::Io { .. } => EXIT_IO_ERROR,
+            EtlError::Parse { .. } | EtlError::ErrorBudgetExceeded { .. } => EXIT_DATA_ERROR,
+            _ => EXIT_FATAL,
+        }
+    }
+}
+
+impl fmt::Display for EtlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EtlError::Io {
+                action,
+                path,
+                source,
+            } => write!(f, "{} {}: {}", action, path.display(), source),
+            EtlError::Parse {
+                file,
+                line,
+                column,
+                source,
+            } => write!(
+                f,
+                "{}:{}:{}: {}",
+                file.display(),
+                line,
+                column,
+                message_without_position(source)
+            ),
+            EtlError::InvalidFilter { message, .. } | EtlError::Sink { message, .. } => {
+                f.write_str(message)
+            }
+            EtlError::Config(message) => f.write_str(message),
+            EtlError::ErrorBudgetExceeded { limits } => {
+                write!(f, "error budget exceeded ({})", limits)
+            }
+        }
+    }
+}
+
+impl Error for EtlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EtlError::Io { source, .. } => Some(source),
+            EtlError::Parse { source, .. } => Some(source),
+            EtlError::InvalidFilter { source, .. } | EtlError::Sink { source, .. } => source
+                .as_deref()
+                .map(|source| source as &(dyn Error + 'static)),
+            EtlError::Config(_) | EtlError::ErrorBudgetExceeded { .. } => None,
+        }
+    }
+}
+
+/// serde_json appends " at line X column Y" relative to the single line being parsed;
+/// [`EtlError::Parse`] already reports the position in the file.
+fn message_without_position(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    let suffix = format!(" at line {} column {}", error.line(), error.column());
+    match message.strip_suffix(&suffix) {
+        Some(stripped) => stripped.to_string(),
+        None => message,
+    }
+}
@@ -1,19 +1,53 @@
+use crate::EtlError;
+use crate::extract::sink::EventSink;
 use crate::model::github::GitHubEvent;
 use std::collections::HashMap;
 
-pub fn count_events(events: &[GitHubEvent]) -> HashMap<String, usize> {
-    let mut counts = HashMap::new();
+/// Running per-type event counts, fed batch by batch so stats never need the full event list.
+#[derive(Debug, Default)]
+pub struct EventCounter {
+    counts: HashMap<String, usize>,
+}
+
+impl EventCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, event: &GitHubEvent) {
+        let type_name = event.event_type.as_str();
+        match self.counts.get_mut(type_name) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert(type_name.to_string(), 1);
+            }
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
 
-    for event in events {
-        let type_name = serde_json::to_value(&event.event_type)
-            .ok()
-            .and_then(|v| v.as_str().map(|s| s.to_string()))
-            .unwrap_or_else(|| "UnknownEvent".to_string());
+    pub fn counts(&self) -> &HashMap<String, usize> {
+        &self.counts
+    }
 
-        *counts.entry(type_name).or_insert(0) += 1;
+    pub fn into_counts(self) -> HashMap<String, usize> {
+        self.counts
     }
+}
 
-    counts
+impl EventSink for EventCounter {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        events.iter().for_each(|event| self.add(event));
+        Ok(())
+    }
+}
+
+pub fn count_events(events: &[GitHubEvent]) -> HashMap<String, usize> {
+    let mut counter = EventCounter::new();
+    events.iter().for_each(|event| counter.add(event));
+    counter.into_counts()
 }
 
 pub fn print_stats(counts: &HashMap<String, usize>) {
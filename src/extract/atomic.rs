@@ -0,0 +1,107 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempPath;
+
+/// A file written under a temporary name next to its target and renamed over it by
+/// [`AtomicFile::commit`]. Readers see either the previous file or the complete new one;
+/// dropping an uncommitted `AtomicFile` removes the temporary file and leaves the target untouched.
+#[derive(Debug)]
+pub struct AtomicFile {
+    target: PathBuf,
+    /// `None` once [`AtomicFile::close`]d.
+    file: Option<File>,
+    temp: TempPath,
+}
+
+impl AtomicFile {
+    /// Creates the hidden sibling `.<name>.<random>.tmp` of `target`.
+    pub fn create(target: impl AsRef<Path>) -> io::Result<Self> {
+        let target = target.as_ref().to_path_buf();
+        let name = target
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+        let dir = parent_dir(&target);
+
+        let prefix = format!(".{}.", name);
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(&prefix).suffix(".tmp");
+        if let Some(permissions) = target_permissions(&target) {
+            builder.permissions(permissions);
+        }
+        let (file, temp) = builder.tempfile_in(dir)?.into_parts();
+
+        Ok(AtomicFile {
+            target,
+            file: Some(file),
+            temp,
+        })
+    }
+
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// A handle writing to the temporary file.
+    pub fn file(&self) -> io::Result<File> {
+        match &self.file {
+            Some(file) => file.try_clone(),
+            None => Err(io::Error::other("temporary file is already closed")),
+        }
+    }
+
+    /// Flushes the data to disk and closes the temporary file, which stays in place until
+    /// it is committed or dropped.
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.file.take() {
+            Some(file) => file.sync_all(),
+            None => Ok(()),
+        }
+    }
+
+    /// Flushes the data to disk, renames the temporary file over the target and syncs
+    /// the directory so the rename itself survives a crash.
+    pub fn commit(mut self) -> io::Result<()> {
+        self.close()?;
+        let dir = parent_dir(&self.target).to_path_buf();
+        self.temp.persist(&self.target).map_err(|e| e.error)?;
+        sync_dir(&dir)
+    }
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Keeps the permissions of the file being replaced; new files get the usual `rw-r--r--`
+/// instead of the owner-only mode of temporary files.
+#[cfg(unix)]
+fn target_permissions(target: &Path) -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(
+        fs::metadata(target)
+            .map(|metadata| metadata.permissions())
+            .unwrap_or_else(|_| fs::Permissions::from_mode(0o644)),
+    )
+}
+
+#[cfg(not(unix))]
+fn target_permissions(target: &Path) -> Option<fs::Permissions> {
+    fs::metadata(target)
+        .ok()
+        .map(|metadata| metadata.permissions())
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
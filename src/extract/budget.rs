@@ -0,0 +1,112 @@
+use crate::EtlError;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Shared error accounting for a run. Readers on any thread report parsed lines,
+/// rejected lines and failed files; the budget trips once the rejected lines exceed
+/// `max_errors` or the rejected share of parsed lines exceeds `max_error_rate`.
+/// The rate only covers files read to the end, so a file still in progress cannot trip it early.
+#[derive(Debug, Default)]
+pub struct ErrorBudget {
+    max_errors: Option<usize>,
+    max_error_rate: Option<f64>,
+    fail_fast: bool,
+    lines: AtomicUsize,
+    errors: AtomicUsize,
+    settled_errors: AtomicUsize,
+    failed_files: AtomicUsize,
+    exceeded: AtomicBool,
+}
+
+impl ErrorBudget {
+    pub fn new(
+        max_errors: Option<usize>,
+        max_error_rate: Option<f64>,
+        fail_fast: bool,
+    ) -> Result<Self, EtlError> {
+        if let Some(rate) = max_error_rate
+            && !(0.0..=1.0).contains(&rate)
+        {
+            return Err(EtlError::Config(format!(
+                "max_error_rate must be between 0 and 1, got {}",
+                rate
+            )));
+        }
+
+        Ok(ErrorBudget {
+            max_errors,
+            max_error_rate,
+            fail_fast,
+            ..Default::default()
+        })
+    }
+
+    /// Records one rejected line. The count limit is checked right away so that
+    /// fail-fast runs stop at the first line over the limit.
+    pub fn record_error(&self) {
+        let errors = self.errors.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.max_errors.is_some_and(|max| errors > max) {
+            self.exceeded.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Records a file read to the end and re-checks the error rate.
+    pub fn record_file(&self, lines: usize, rejected: usize) {
+        self.lines.fetch_add(lines, Ordering::Relaxed);
+        self.settled_errors.fetch_add(rejected, Ordering::Relaxed);
+        self.check_rate();
+    }
+
+    pub fn record_failed_file(&self) {
+        self.failed_files.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn check_rate(&self) {
+        let Some(max_rate) = self.max_error_rate else {
+            return;
+        };
+        let lines = self.lines.load(Ordering::Relaxed);
+        let errors = self.settled_errors.load(Ordering::Relaxed);
+        if lines > 0 && errors as f64 / lines as f64 > max_rate {
+            self.exceeded.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::Relaxed)
+    }
+
+    /// True when the run should stop reading because the budget tripped in fail-fast mode.
+    pub fn should_abort(&self) -> bool {
+        self.fail_fast && self.is_exceeded()
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines.load(Ordering::Relaxed)
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    pub fn failed_files(&self) -> usize {
+        self.failed_files.load(Ordering::Relaxed)
+    }
+
+    /// Error used to stop reading once [`Self::should_abort`] is true.
+    pub fn exceeded_error(&self) -> EtlError {
+        EtlError::ErrorBudgetExceeded {
+            limits: self.describe_limits(),
+        }
+    }
+
+    pub fn describe_limits(&self) -> String {
+        let mut limits = Vec::new();
+        if let Some(max) = self.max_errors {
+            limits.push(format!("max {} errors", max));
+        }
+        if let Some(rate) = self.max_error_rate {
+            limits.push(format!("max error rate {}", rate));
+        }
+        limits.join(", ")
+    }
+}
@@ -0,0 +1,385 @@
+use crate::extract::atomic::AtomicFile;
+use crate::extract::partition;
+use crate::{Config, EtlError, OutputFormat};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the checkpoint and incremental state formats.
+pub const STATE_VERSION: u32 = 1;
+
+/// What a checkpoint remembers of an input file that was fully processed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    pub sha256: String,
+}
+
+/// Size and modification time of a file.
+type Stamp = (u64, DateTime<Utc>);
+
+fn stamp(path: &Path) -> io::Result<Stamp> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.len(), metadata.modified()?.into()))
+}
+
+impl FileRecord {
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let (size, modified) = stamp(path)?;
+        Ok(FileRecord {
+            size,
+            modified,
+            sha256: sha256(path)?,
+        })
+    }
+
+    /// Same size and modification time, or same content for a file that was only touched.
+    fn matches(&self, path: &Path) -> bool {
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+        if metadata.len() != self.size {
+            return false;
+        }
+        let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+        modified == Some(self.modified) || sha256(path).is_ok_and(|hash| hash == self.sha256)
+    }
+}
+
+/// A record of what earlier runs committed, used to pick the inputs of the next run.
+/// Runs keeping one append to their outputs in place instead of replacing them.
+pub trait ProgressLog {
+    /// Leaves out the inputs an earlier run already covered.
+    fn select(&mut self, files: Vec<PathBuf>) -> Vec<PathBuf>;
+
+    /// Prepares the outputs before they are appended to.
+    fn rewind(&self) -> Result<(), EtlError>;
+
+    /// Marks an input as completely written to the outputs; it is recorded by the next save.
+    fn file_done(&mut self, path: &Path);
+
+    /// Records the progress so far. The outputs must have been made durable first.
+    fn save(&mut self) -> Result<(), EtlError>;
+
+    /// Cuts the event output back to its length at the last save.
+    fn discard_output(&self) -> Result<(), EtlError>;
+}
+
+/// The outputs a progress file was written for, and the lengths of the single-file ones
+/// when it was saved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutputLengths {
+    pub output: Option<String>,
+    pub output_bytes: Option<u64>,
+    pub reject_file: Option<String>,
+    pub reject_bytes: Option<u64>,
+}
+
+impl OutputLengths {
+    pub fn new(config: &Config) -> Self {
+        OutputLengths {
+            output: config.output_file.clone(),
+            reject_file: config.reject_file.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Rejects a progress file that belongs to other outputs.
+    pub fn check(&self, config: &Config, state_path: &Path) -> Result<(), EtlError> {
+        if self.output == config.output_file && self.reject_file == config.reject_file {
+            return Ok(());
+        }
+        Err(EtlError::Config(format!(
+            "{} was written for output {} and reject file {}",
+            state_path.display(),
+            self.output.as_deref().unwrap_or("(none)"),
+            self.reject_file.as_deref().unwrap_or("(none)")
+        )))
+    }
+}
+
+/// The outputs of a run that are single files appended to in place, and can therefore be
+/// cut back to a recorded length. A database or a partition directory is not one of them.
+#[derive(Debug, Clone, Default)]
+pub struct AppendedFiles {
+    output: Option<PathBuf>,
+    reject_file: Option<PathBuf>,
+}
+
+impl AppendedFiles {
+    pub fn of(config: &Config) -> Self {
+        let single_file =
+            config.output_format != OutputFormat::Sqlite && config.partition_by.is_empty();
+        AppendedFiles {
+            output: config
+                .output_file
+                .as_ref()
+                .filter(|_| single_file)
+                .map(PathBuf::from),
+            reject_file: config.reject_file.as_ref().map(PathBuf::from),
+        }
+    }
+
+    /// Stores the current lengths in `lengths`.
+    pub fn measure(&self, lengths: &mut OutputLengths) -> Result<(), EtlError> {
+        lengths.output_bytes = self.output.as_deref().map(file_len).transpose()?;
+        lengths.reject_bytes = self.reject_file.as_deref().map(file_len).transpose()?;
+        Ok(())
+    }
+
+    /// Cuts both files back to the recorded lengths, empties them if none are recorded.
+    pub fn truncate_to(&self, lengths: &OutputLengths) -> Result<(), EtlError> {
+        self.truncate_output_to(lengths)?;
+        match &self.reject_file {
+            Some(reject_file) => truncate(reject_file, lengths.reject_bytes.unwrap_or(0)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn truncate_output_to(&self, lengths: &OutputLengths) -> Result<(), EtlError> {
+        match &self.output {
+            Some(output) => truncate(output, lengths.output_bytes.unwrap_or(0)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Empties the outputs of a run that appends to them without a progress log to resume from.
+pub fn start_over(config: &Config) -> Result<(), EtlError> {
+    AppendedFiles::of(config).truncate_to(&OutputLengths::default())?;
+    match &config.output_file {
+        Some(root) if !config.partition_by.is_empty() => {
+            partition::remove_all_parts(Path::new(root))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reads a JSON progress file of the current version, `None` if there is none yet.
+pub fn load_state<T: DeserializeOwned>(
+    path: &Path,
+    version: impl Fn(&T) -> u32,
+) -> Result<Option<T>, EtlError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(EtlError::io("Failed to read state file", path, e)),
+    };
+    let state: T = serde_json::from_str(&content)
+        .map_err(|e| EtlError::Config(format!("Invalid state file {}: {}", path.display(), e)))?;
+    if version(&state) != STATE_VERSION {
+        return Err(EtlError::Config(format!(
+            "State file {} has unsupported version {}",
+            path.display(),
+            version(&state)
+        )));
+    }
+    Ok(Some(state))
+}
+
+/// Replaces a JSON progress file atomically.
+pub fn save_state<T: Serialize>(path: &Path, state: &T) -> Result<(), EtlError> {
+    let json = serde_json::to_vec_pretty(state).map_err(|e| {
+        EtlError::sink_caused_by(format!("Failed to serialize state file: {}", e), e)
+    })?;
+    AtomicFile::create(path)
+        .and_then(|file| {
+            file.file()?.write_all(&json)?;
+            file.commit()
+        })
+        .map_err(|e| EtlError::io("Failed to write state file", path, e))
+}
+
+/// Contents of a checkpoint file: the inputs committed to the outputs so far.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    #[serde(flatten)]
+    pub outputs: OutputLengths,
+    /// Keyed by absolute path.
+    pub files: BTreeMap<String, FileRecord>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Option<Self>, EtlError> {
+        load_state(path, |manifest: &Manifest| manifest.version)
+    }
+}
+
+/// Progress of a checkpointed run. Outputs are appended to in place and made durable,
+/// together with the list of finished inputs, every time the progress is saved.
+/// A resumed run skips the recorded inputs and cuts the outputs back to their recorded
+/// length, dropping whatever a crashed run wrote after its last checkpoint.
+pub struct Checkpoint {
+    path: PathBuf,
+    manifest: Manifest,
+    appended: AppendedFiles,
+    /// Partition directory emptied when a run starts over.
+    partition_root: Option<PathBuf>,
+    resumed: bool,
+    /// Inputs of this run as they were before they were read.
+    selected: HashMap<PathBuf, Stamp>,
+    finished: Vec<PathBuf>,
+}
+
+impl Checkpoint {
+    /// Reads the checkpoint of `config` when resuming, or starts an empty one.
+    pub fn load(checkpoint_path: &str, config: &Config) -> Result<Self, EtlError> {
+        if config.output_format == OutputFormat::Parquet && config.output_file.is_some() {
+            return Err(EtlError::Config(
+                "Parquet output cannot be checkpointed, it is only complete once the run ends"
+                    .to_string(),
+            ));
+        }
+        let path = PathBuf::from(checkpoint_path);
+        let loaded = if config.resume {
+            let manifest = Manifest::load(&path)?;
+            if manifest.is_none() {
+                return Err(EtlError::Config(format!(
+                    "Cannot resume: checkpoint file {} does not exist",
+                    path.display()
+                )));
+            }
+            manifest
+        } else {
+            None
+        };
+        let resumed = loaded.is_some();
+        let manifest = loaded.unwrap_or_else(|| Manifest {
+            version: STATE_VERSION,
+            outputs: OutputLengths::new(config),
+            files: BTreeMap::new(),
+        });
+        manifest.outputs.check(config, &path)?;
+
+        Ok(Checkpoint {
+            path,
+            manifest,
+            appended: AppendedFiles::of(config),
+            partition_root: config
+                .output_file
+                .as_ref()
+                .filter(|_| !config.partition_by.is_empty())
+                .map(PathBuf::from),
+            resumed,
+            selected: HashMap::new(),
+            finished: Vec::new(),
+        })
+    }
+
+    /// True if `path` was committed by an earlier run and has not changed since.
+    pub fn is_done(&self, path: &Path) -> bool {
+        let Ok(absolute) = fs::canonicalize(path) else {
+            return false;
+        };
+        self.manifest
+            .files
+            .get(&absolute.display().to_string())
+            .is_some_and(|record| record.matches(path))
+    }
+}
+
+impl ProgressLog for Checkpoint {
+    fn select(&mut self, mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+        files.retain(|path| !self.is_done(path));
+        for path in &files {
+            if let Ok(stamp) = stamp(path) {
+                self.selected.insert(path.clone(), stamp);
+            }
+        }
+        files
+    }
+
+    /// Recorded lengths when resuming, empty outputs when starting over.
+    fn rewind(&self) -> Result<(), EtlError> {
+        self.appended.truncate_to(&self.manifest.outputs)?;
+        if !self.resumed
+            && let Some(root) = &self.partition_root
+        {
+            partition::remove_all_parts(root)?;
+        }
+        Ok(())
+    }
+
+    fn file_done(&mut self, path: &Path) {
+        self.finished.push(path.to_path_buf());
+    }
+
+    /// An input that changed since it was selected is left for the next run, as what was
+    /// read of it may be neither the old nor the new content.
+    fn save(&mut self) -> Result<(), EtlError> {
+        for path in self.finished.drain(..) {
+            let fingerprint_error = |e| EtlError::io("Failed to fingerprint input file", &path, e);
+            let before = self.selected.remove(&path);
+            if before != Some(stamp(&path).map_err(fingerprint_error)?) {
+                eprintln!(
+                    "Warning: {} changed while it was processed, it is not checkpointed",
+                    path.display()
+                );
+                continue;
+            }
+            let record = FileRecord::of(&path).map_err(fingerprint_error)?;
+            let absolute = fs::canonicalize(&path).map_err(fingerprint_error)?;
+            self.manifest
+                .files
+                .insert(absolute.display().to_string(), record);
+        }
+        self.appended.measure(&mut self.manifest.outputs)?;
+        save_state(&self.path, &self.manifest)
+    }
+
+    fn discard_output(&self) -> Result<(), EtlError> {
+        self.appended.truncate_output_to(&self.manifest.outputs)
+    }
+}
+
+/// Length of `path`, 0 if it does not exist yet.
+fn file_len(path: &Path) -> Result<u64, EtlError> {
+    match fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(EtlError::io("Failed to read output file", path, e)),
+    }
+}
+
+/// Creates `path` if needed and shortens it to `len` bytes. A file shorter than recorded
+/// was changed by something else and is not touched.
+fn truncate(path: &Path, len: u64) -> Result<(), EtlError> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| EtlError::io("Failed to open output file", path, e))?;
+    let current = file
+        .metadata()
+        .map_err(|e| EtlError::io("Failed to open output file", path, e))?
+        .len();
+    if current < len {
+        return Err(EtlError::Config(format!(
+            "{} is shorter than recorded in the state file ({} < {} bytes), it was modified since",
+            path.display(),
+            current,
+            len
+        )));
+    }
+    file.set_len(len)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| EtlError::io("Failed to truncate output file", path, e))
+}
+
+fn sha256(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
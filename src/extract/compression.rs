@@ -0,0 +1,240 @@
+use crate::EtlError;
+use bzip2::read::MultiBzDecoder;
+use bzip2::write::BzEncoder;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::File as StdFile;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Recognizes a compressed stream by its magic bytes.
+    pub fn from_magic(head: &[u8]) -> Compression {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else if head.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else {
+            Compression::None
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Compression {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            Some("bz2") => Compression::Bzip2,
+            _ => Compression::None,
+        }
+    }
+
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+            Compression::Bzip2 => Some("bz2"),
+        }
+    }
+
+    fn level_range(&self) -> (i32, i32) {
+        match self {
+            Compression::None => (0, 0),
+            Compression::Gzip => (0, 9),
+            Compression::Zstd => (1, 22),
+            Compression::Bzip2 => (1, 9),
+        }
+    }
+
+    fn default_level(&self) -> i32 {
+        match self {
+            Compression::None => 0,
+            Compression::Gzip => 6,
+            Compression::Zstd => 3,
+            Compression::Bzip2 => 9,
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = EtlError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "none" => Ok(Compression::None),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            "bzip2" | "bz2" => Ok(Compression::Bzip2),
+            _ => Err(EtlError::Config(format!(
+                "Invalid compression '{}'. Valid values are: none, gzip, zstd, bzip2",
+                value
+            ))),
+        }
+    }
+}
+
+/// Returns the file name with the `.json` and compression extensions removed,
+/// e.g. `2015-01-01-15.json.gz` -> `2015-01-01-15`.
+pub fn archive_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    let name = match Compression::from_path(path).extension() {
+        Some(ext) => name.strip_suffix(ext)?.strip_suffix('.')?,
+        None => name,
+    };
+    name.strip_suffix(".json")
+}
+
+/// True for `.json` files and their `.gz`, `.zst` and `.bz2` compressed forms.
+pub fn is_supported_input(path: &Path) -> bool {
+    archive_stem(path).is_some()
+}
+
+/// Opens a file for line reading, decompressing it on the fly based on its extension.
+pub fn open_reader(path: &Path) -> io::Result<Box<dyn BufRead + Send>> {
+    decompress(StdFile::open(path)?, Compression::from_path(path))
+}
+
+/// Wraps `reader` in the decoder of `codec`.
+pub fn decompress(
+    reader: impl Read + Send + 'static,
+    codec: Compression,
+) -> io::Result<Box<dyn BufRead + Send>> {
+    let reader: Box<dyn BufRead + Send> = match codec {
+        Compression::None => Box::new(BufReader::new(reader)),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::new(reader)?)),
+        Compression::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+    };
+
+    Ok(reader)
+}
+
+/// Codec and level of a compressed output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputCompression {
+    pub codec: Compression,
+    pub level: i32,
+}
+
+impl OutputCompression {
+    /// Uses `codec` when given, the extension of `path` otherwise, and the codec's
+    /// default level unless `level` is set.
+    pub fn resolve(
+        path: &Path,
+        codec: Option<Compression>,
+        level: Option<i32>,
+    ) -> Result<Self, EtlError> {
+        let codec = codec.unwrap_or_else(|| Compression::from_path(path));
+        let (min, max) = codec.level_range();
+        match level {
+            Some(_) if codec == Compression::None => Err(EtlError::Config(
+                "A compression level needs a compressed output (--compress or a .gz, .zst or .bz2 output path)"
+                    .to_string(),
+            )),
+            Some(level) if !(min..=max).contains(&level) => Err(EtlError::Config(format!(
+                "Invalid {:?} compression level {}: expected {}..={}",
+                codec, level, min, max
+            ))),
+            _ => Ok(OutputCompression {
+                codec,
+                level: level.unwrap_or_else(|| codec.default_level()),
+            }),
+        }
+    }
+
+    /// Output compression implied by the extension of `path` alone.
+    pub fn from_path(path: &Path) -> Self {
+        let codec = Compression::from_path(path);
+        OutputCompression {
+            codec,
+            level: codec.default_level(),
+        }
+    }
+
+    pub fn writer(&self, file: StdFile) -> io::Result<CompressedWriter> {
+        let encoder = match self.codec {
+            Compression::None => Encoder::Plain(file),
+            Compression::Gzip => Encoder::Gzip(GzEncoder::new(
+                file,
+                flate2::Compression::new(self.level as u32),
+            )),
+            Compression::Zstd => {
+                Encoder::Zstd(zstd::stream::write::Encoder::new(file, self.level)?)
+            }
+            Compression::Bzip2 => Encoder::Bzip2(BzEncoder::new(
+                file,
+                bzip2::Compression::new(self.level as u32),
+            )),
+        };
+        Ok(CompressedWriter {
+            inner: BufWriter::new(encoder),
+        })
+    }
+}
+
+enum Encoder {
+    Plain(StdFile),
+    Gzip(GzEncoder<StdFile>),
+    Zstd(zstd::stream::write::Encoder<'static, StdFile>),
+    Bzip2(BzEncoder<StdFile>),
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(file) => file.write(buf),
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+            Encoder::Bzip2(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(file) => file.flush(),
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+            Encoder::Bzip2(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Buffered writer compressing into a file. [`CompressedWriter::finish`] must be called
+/// to write the end of the compressed stream; dropping the writer loses it.
+pub struct CompressedWriter {
+    inner: BufWriter<Encoder>,
+}
+
+impl CompressedWriter {
+    /// Flushes the buffer, ends the compressed stream and returns the underlying file.
+    pub fn finish(self) -> io::Result<StdFile> {
+        let encoder = self.inner.into_inner().map_err(|e| e.into_error())?;
+        match encoder {
+            Encoder::Plain(file) => Ok(file),
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Zstd(encoder) => encoder.finish(),
+            Encoder::Bzip2(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
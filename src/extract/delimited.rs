@@ -0,0 +1,196 @@
+use crate::EtlError;
+use crate::extract::atomic::AtomicFile;
+use crate::extract::compression::{CompressedWriter, OutputCompression};
+use crate::extract::sink::EventSink;
+use crate::model::fields::{self, Field};
+use crate::model::github::GitHubEvent;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// Columns written when `--columns` is not given.
+pub const DEFAULT_COLUMNS: &[&str] = &["id", "type", "actor.login", "repo.name", "created_at"];
+
+/// CSV or TSV file with a header row and one column per dotted field path.
+/// Values containing the delimiter, quotes or newlines are quoted; missing values are empty.
+pub struct DelimitedSink {
+    path: PathBuf,
+    /// Resolved once, in column order.
+    fields: Vec<Field>,
+    delimiter: u8,
+    compression: OutputCompression,
+    writer: Option<csv::Writer<CompressedWriter>>,
+    pending: Option<AtomicFile>,
+}
+
+impl DelimitedSink {
+    /// Starts a new table with its header row, written through an [`AtomicFile`] so that
+    /// `output_path` keeps its previous rows until the sink is finished.
+    pub fn create(
+        output_path: &str,
+        columns: &[String],
+        delimiter: u8,
+        compression: OutputCompression,
+    ) -> Result<Self, EtlError> {
+        let fields = resolve_columns(columns)?;
+        let pending = AtomicFile::create(output_path)
+            .map_err(|e| EtlError::io("Failed to create output file", output_path, e))?;
+        let file = pending
+            .file()
+            .map_err(|e| EtlError::io("Failed to create output file", output_path, e))?;
+        Self::start(
+            output_path,
+            fields,
+            delimiter,
+            compression,
+            file,
+            Some(pending),
+            true,
+        )
+    }
+
+    /// Appends rows to `output_path`, writing the header only if the file is empty.
+    pub fn append(
+        output_path: &str,
+        columns: &[String],
+        delimiter: u8,
+        compression: OutputCompression,
+    ) -> Result<Self, EtlError> {
+        let fields = resolve_columns(columns)?;
+        let file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(output_path)
+            .map_err(|e| EtlError::io("Failed to open output file", output_path, e))?;
+        let is_empty = file
+            .metadata()
+            .map_err(|e| EtlError::io("Failed to open output file", output_path, e))?
+            .len()
+            == 0;
+        Self::start(
+            output_path,
+            fields,
+            delimiter,
+            compression,
+            file,
+            None,
+            is_empty,
+        )
+    }
+
+    fn start(
+        output_path: &str,
+        fields: Vec<Field>,
+        delimiter: u8,
+        compression: OutputCompression,
+        file: File,
+        pending: Option<AtomicFile>,
+        write_header: bool,
+    ) -> Result<Self, EtlError> {
+        let mut writer = compression
+            .writer(file)
+            .map(|file| csv_writer(file, delimiter))
+            .map_err(|e| EtlError::io("Failed to open output file", output_path, e))?;
+        if write_header {
+            writer
+                .write_record(fields.iter().map(Field::path))
+                .map_err(|e| csv_error(Path::new(output_path), e))?;
+        }
+
+        Ok(DelimitedSink {
+            path: PathBuf::from(output_path),
+            fields,
+            delimiter,
+            compression,
+            writer: Some(writer),
+            pending,
+        })
+    }
+}
+
+impl EventSink for DelimitedSink {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(EtlError::sink(format!(
+                "Output {} was already finished",
+                self.path.display()
+            )));
+        };
+        for event in events {
+            for field in &self.fields {
+                let value = field.read(event);
+                let text = value.to_text().unwrap_or_default();
+                writer
+                    .write_field(text.as_bytes())
+                    .map_err(|e| csv_error(&self.path, e))?;
+            }
+            writer
+                .write_record(None::<&[u8]>)
+                .map_err(|e| csv_error(&self.path, e))?;
+        }
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) -> Result<(), EtlError> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let file = writer
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(CompressedWriter::finish)
+            .and_then(|file| file.sync_all().map(|_| file))
+            .and_then(|file| self.compression.writer(file))
+            .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))?;
+        self.writer = Some(csv_writer(file, self.delimiter));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), EtlError> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let pending = self.pending.take();
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(CompressedWriter::finish)
+            .and_then(|file| file.sync_all())
+            .and_then(|_| pending.map_or(Ok(()), AtomicFile::commit))
+            .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))
+    }
+}
+
+fn csv_writer(file: CompressedWriter, delimiter: u8) -> csv::Writer<CompressedWriter> {
+    csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(file)
+}
+
+/// Resolves the column paths, rejecting those the model does not have and listing the
+/// valid ones.
+fn resolve_columns(columns: &[String]) -> Result<Vec<Field>, EtlError> {
+    let columns = if columns.is_empty() {
+        DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect()
+    } else {
+        columns.to_vec()
+    };
+    columns
+        .iter()
+        .map(|column| {
+            Field::resolve(column).ok_or_else(|| {
+                EtlError::Config(format!(
+                    "Unknown column '{}'. Valid columns are: {}, or any path below an untyped payload member",
+                    column,
+                    fields::known_fields().collect::<Vec<_>>().join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+fn csv_error(path: &Path, error: csv::Error) -> EtlError {
+    EtlError::sink_caused_by(
+        format!("Failed to write to file {}: {}", path.display(), error),
+        error,
+    )
+}
@@ -0,0 +1,319 @@
+use crate::extract::compression::{archive_stem, is_supported_input};
+use crate::{Config, EtlError};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use globset::{GlobBuilder, GlobMatcher};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Order in which the input files are read, and so the order of the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputOrder {
+    /// Natural order of the paths: `events-2.json` before `events-10.json`.
+    Name,
+    /// GH Archive hours chronologically, followed by the other files in name order.
+    #[default]
+    Time,
+    /// Smallest file first, files of equal size in name order.
+    Size,
+    /// Whatever order the folders list them in, which is not deterministic.
+    None,
+}
+
+impl InputOrder {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputOrder::Name => "name",
+            InputOrder::Time => "time",
+            InputOrder::Size => "size",
+            InputOrder::None => "none",
+        }
+    }
+}
+
+impl FromStr for InputOrder {
+    type Err = EtlError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "name" => Ok(InputOrder::Name),
+            "time" => Ok(InputOrder::Time),
+            "size" => Ok(InputOrder::Size),
+            "none" => Ok(InputOrder::None),
+            _ => Err(EtlError::Config(format!(
+                "Invalid input order '{}'. Valid orders are: name, time, size, none",
+                value
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for InputOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A glob over input paths. A pattern containing `/` matches the path relative to its input
+/// root, any other pattern the file name alone; `*` never crosses `/`, `**` does.
+#[derive(Debug, Clone)]
+pub struct PathGlob {
+    matcher: GlobMatcher,
+    whole_path: bool,
+}
+
+impl PathGlob {
+    pub fn parse(pattern: &str) -> Result<Self, EtlError> {
+        GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map(|glob| PathGlob {
+                matcher: glob.compile_matcher(),
+                whole_path: pattern.contains('/'),
+            })
+            .map_err(|e| {
+                EtlError::invalid_filter_caused_by(
+                    format!("Invalid path pattern '{}': {}", pattern, e),
+                    e,
+                )
+            })
+    }
+
+    pub fn matches(&self, relative: &Path) -> bool {
+        if self.whole_path {
+            self.matcher.is_match(relative)
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.matcher.is_match(name))
+        }
+    }
+}
+
+/// Where the input files of a run are looked for and which of them are read.
+#[derive(Debug, Clone, Default)]
+pub struct InputDiscovery {
+    /// Folders to list, or single files to read.
+    pub roots: Vec<PathBuf>,
+    /// Also list the subfolders of the roots, following symbolic links.
+    pub recursive: bool,
+    /// When not empty, only files matching one of these are read.
+    pub include: Vec<PathGlob>,
+    pub exclude: Vec<PathGlob>,
+    /// GH Archive files whose hour lies entirely outside `[since, until)` are left out.
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub order: InputOrder,
+}
+
+impl InputDiscovery {
+    pub fn from_config(
+        config: &Config,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Self, EtlError> {
+        let parse_all = |patterns: &[String]| -> Result<Vec<PathGlob>, EtlError> {
+            patterns
+                .iter()
+                .map(|pattern| PathGlob::parse(pattern))
+                .collect()
+        };
+        Ok(InputDiscovery {
+            roots: std::iter::once(&config.path_to_data)
+                .chain(&config.extra_paths)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            recursive: config.recursive,
+            include: parse_all(&config.include)?,
+            exclude: parse_all(&config.exclude)?,
+            since,
+            until,
+            order: config.input_order,
+        })
+    }
+
+    /// Lists the supported input files under all roots in `order`. A file named as a root is
+    /// read whatever its name. A file reached twice, through overlapping roots or symbolic
+    /// links, is listed once.
+    pub fn list(&self) -> Result<Vec<PathBuf>, EtlError> {
+        let mut files = Vec::new();
+        let mut seen_files = HashSet::new();
+        let mut seen_dirs = HashSet::new();
+        for root in &self.roots {
+            if root.is_file() {
+                if seen_files.insert(canonical_file(root)) {
+                    files.push(root.clone());
+                }
+            } else {
+                self.list_dir(root, root, &mut files, &mut seen_files, &mut seen_dirs)?;
+            }
+        }
+        sort_files(&mut files, self.order);
+        Ok(files)
+    }
+
+    fn list_dir(
+        &self,
+        root: &Path,
+        dir: &Path,
+        files: &mut Vec<PathBuf>,
+        seen_files: &mut HashSet<PathBuf>,
+        seen_dirs: &mut HashSet<PathBuf>,
+    ) -> Result<(), EtlError> {
+        // A directory reached again through a symbolic link would be listed forever.
+        let canonical =
+            fs::canonicalize(dir).map_err(|e| EtlError::io("Unable to read folder", dir, e))?;
+        if !seen_dirs.insert(canonical) {
+            return Ok(());
+        }
+        let entries =
+            fs::read_dir(dir).map_err(|e| EtlError::io("Unable to read folder", dir, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| EtlError::io("Unable to read folder", dir, e))?;
+            let path = entry.path();
+            if path.is_file() {
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                self.add_file(&path, relative, files, seen_files);
+            } else if self.recursive && path.is_dir() {
+                self.list_dir(root, &path, files, seen_files, seen_dirs)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_file(
+        &self,
+        path: &Path,
+        relative: &Path,
+        files: &mut Vec<PathBuf>,
+        seen_files: &mut HashSet<PathBuf>,
+    ) {
+        let selected = is_supported_input(path)
+            && overlaps_window(path, self.since, self.until)
+            && (self.include.is_empty() || self.include.iter().any(|glob| glob.matches(relative)))
+            && !self.exclude.iter().any(|glob| glob.matches(relative));
+        if selected && seen_files.insert(canonical_file(path)) {
+            files.push(path.to_path_buf());
+        }
+    }
+}
+
+fn canonical_file(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Lists the supported input files at the top level of a folder in `order`, skipping GH
+/// Archive files whose hour lies entirely outside `[since, until)`.
+pub fn list_input_files(
+    folder_path: &str,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    order: InputOrder,
+) -> Result<Vec<PathBuf>, EtlError> {
+    InputDiscovery {
+        roots: vec![PathBuf::from(folder_path)],
+        since,
+        until,
+        order,
+        ..Default::default()
+    }
+    .list()
+}
+
+pub fn sort_files(files: &mut Vec<PathBuf>, order: InputOrder) {
+    match order {
+        InputOrder::Name => files.sort_by(|a, b| compare_paths(a, b)),
+        InputOrder::Time => files.sort_by(|a, b| {
+            let (hour_a, hour_b) = (archive_hour(a), archive_hour(b));
+            // Files with an hour come first.
+            (hour_a.is_none(), hour_a)
+                .cmp(&(hour_b.is_none(), hour_b))
+                .then_with(|| compare_paths(a, b))
+        }),
+        InputOrder::Size => {
+            let mut sized: Vec<(u64, PathBuf)> = files
+                .drain(..)
+                .map(|path| (fs::metadata(&path).map_or(0, |m| m.len()), path))
+                .collect();
+            sized.sort_by(|(size_a, a), (size_b, b)| {
+                size_a.cmp(size_b).then_with(|| compare_paths(a, b))
+            });
+            files.extend(sized.into_iter().map(|(_, path)| path));
+        }
+        InputOrder::None => {}
+    }
+}
+
+fn compare_paths(a: &Path, b: &Path) -> Ordering {
+    natural_cmp(&a.to_string_lossy(), &b.to_string_lossy())
+}
+
+/// Compares runs of digits by their numeric value and everything else character by
+/// character, so `2015-01-01-9` sorts before `2015-01-01-10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut x, mut y) = (a, b);
+    loop {
+        match (x.chars().next(), y.chars().next()) {
+            // Equal up to leading zeros.
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(c), Some(d)) if c.is_ascii_digit() && d.is_ascii_digit() => {
+                let (digits_x, rest_x) = split_digits(x);
+                let (digits_y, rest_y) = split_digits(y);
+                let (number_x, number_y) = (
+                    digits_x.trim_start_matches('0'),
+                    digits_y.trim_start_matches('0'),
+                );
+                let ordering = number_x
+                    .len()
+                    .cmp(&number_y.len())
+                    .then_with(|| number_x.cmp(number_y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                (x, y) = (rest_x, rest_y);
+            }
+            (Some(c), Some(d)) => {
+                if c != d {
+                    return c.cmp(&d);
+                }
+                (x, y) = (&x[c.len_utf8()..], &y[d.len_utf8()..]);
+            }
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()))
+}
+
+/// Start of the hour covered by a GH Archive file named `YYYY-MM-DD-H.json[.gz]`.
+pub fn archive_hour(path: &Path) -> Option<DateTime<Utc>> {
+    let mut parts = archive_stem(path)?.splitn(4, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    let hour = parts.next()?.parse().ok()?;
+
+    let start = NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, 0, 0)?;
+    Some(start.and_utc())
+}
+
+/// Files without an hour-stamped name are always kept, their events are filtered one by one.
+pub fn overlaps_window(
+    path: &Path,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> bool {
+    let Some(start) = archive_hour(path) else {
+        return true;
+    };
+    let end = start + Duration::hours(1);
+
+    since.is_none_or(|since| end > since) && until.is_none_or(|until| start < until)
+}
@@ -0,0 +1,53 @@
+use crate::EtlError;
+use crate::extract::source::open_input;
+use crate::model::borrowed::GitHubEventRef;
+use std::io::BufRead;
+use std::path::Path;
+
+/// Parses a line into a borrowed event with serde_json.
+#[cfg(not(feature = "simd"))]
+pub fn parse_event_ref(line: &mut [u8]) -> Result<GitHubEventRef<'_>, serde_json::Error> {
+    serde_json::from_slice(line)
+}
+
+/// Parses a line into a borrowed event with simd-json, which unescapes strings in place.
+#[cfg(feature = "simd")]
+pub fn parse_event_ref(line: &mut [u8]) -> Result<GitHubEventRef<'_>, serde_json::Error> {
+    simd_json::serde::from_slice(line).map_err(serde::de::Error::custom)
+}
+
+/// Reads a file through one reused line buffer and hands every line to `handle`, parsed
+/// as a borrowed view that lives until the next line is read, or as its parse error.
+/// Stops at the first error `handle` returns. Returns the number of non-empty lines read.
+///
+/// Only the parse benchmark reads files this way; runs go through
+/// [`for_each_line`](crate::extract::json_lines::for_each_line), which keeps the events
+/// and sends rejected lines to the reject file and the error budget.
+#[doc(hidden)]
+pub fn for_each_event_ref<F>(path: &Path, mut handle: F) -> Result<usize, EtlError>
+where
+    F: FnMut(Result<GitHubEventRef<'_>, EtlError>) -> Result<(), EtlError>,
+{
+    let mut reader =
+        open_input(path).map_err(|e| EtlError::io("Failed to open input file", path, e))?;
+    let mut buffer = Vec::new();
+    let mut line_number = 0usize;
+    let mut lines_read = 0usize;
+
+    loop {
+        buffer.clear();
+        let read = reader
+            .read_until(b'\n', &mut buffer)
+            .map_err(|e| EtlError::io("Failed to read input file", path, e))?;
+        if read == 0 {
+            return Ok(lines_read);
+        }
+        line_number += 1;
+        if buffer.trim_ascii().is_empty() {
+            continue;
+        }
+        lines_read += 1;
+
+        handle(parse_event_ref(&mut buffer).map_err(|e| EtlError::parse(path, line_number, e)))?;
+    }
+}
@@ -0,0 +1,657 @@
+use crate::model::fields::{self, Field, FieldType, FieldValue};
+use crate::model::github::GitHubEvent;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Error in a `--where` expression. `column` is 1-based and counts characters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl ExprError {
+    fn new(column: usize, message: impl Into<String>) -> Self {
+        ExprError {
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Formats the error with the expression and a caret under the offending column.
+    pub fn render(&self, source: &str) -> String {
+        format!(
+            "{}\n  {}\n  {}^",
+            self,
+            source,
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Str(String),
+    Number(f64),
+    True,
+    False,
+    Null,
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Cmp(CmpOp),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+impl CmpOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CmpOp::Eq => "==",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+            CmpOp::Match => "~",
+            CmpOp::NotMatch => "!~",
+        }
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(self, CmpOp::Lt | CmpOp::Le | CmpOp::Gt | CmpOp::Ge)
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExprError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        let next = chars.get(i + 1).copied();
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let (kind, len) = match (c, next) {
+            ('(', _) => (TokenKind::LParen, 1),
+            (')', _) => (TokenKind::RParen, 1),
+            ('&', Some('&')) => (TokenKind::And, 2),
+            ('|', Some('|')) => (TokenKind::Or, 2),
+            ('=', Some('=')) => (TokenKind::Cmp(CmpOp::Eq), 2),
+            ('!', Some('=')) => (TokenKind::Cmp(CmpOp::Ne), 2),
+            ('!', Some('~')) => (TokenKind::Cmp(CmpOp::NotMatch), 2),
+            ('!', _) => (TokenKind::Not, 1),
+            ('<', Some('=')) => (TokenKind::Cmp(CmpOp::Le), 2),
+            ('<', _) => (TokenKind::Cmp(CmpOp::Lt), 1),
+            ('>', Some('=')) => (TokenKind::Cmp(CmpOp::Ge), 2),
+            ('>', _) => (TokenKind::Cmp(CmpOp::Gt), 1),
+            ('~', _) => (TokenKind::Cmp(CmpOp::Match), 1),
+            ('&', _) => return Err(ExprError::new(column, "expected '&&'")),
+            ('|', _) => return Err(ExprError::new(column, "expected '||'")),
+            ('=', _) => return Err(ExprError::new(column, "expected '==' for comparison")),
+            ('"' | '\'', _) => {
+                let (value, len) = lex_string(&chars, i)?;
+                (TokenKind::Str(value), len)
+            }
+            (c, _)
+                if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let len = lex_number_len(&chars, i);
+                let text: String = chars[i..i + len].iter().collect();
+                let value = text
+                    .parse::<f64>()
+                    .map_err(|_| ExprError::new(column, format!("invalid number '{}'", text)))?;
+                (TokenKind::Number(value), len)
+            }
+            (c, _) if c.is_ascii_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric() || **c == '_' || **c == '.')
+                    .count();
+                let word: String = chars[i..i + len].iter().collect();
+                if word.split('.').any(str::is_empty) {
+                    return Err(ExprError::new(
+                        column,
+                        format!("malformed field path '{}'", word),
+                    ));
+                }
+                let kind = match word.as_str() {
+                    "true" => TokenKind::True,
+                    "false" => TokenKind::False,
+                    "null" => TokenKind::Null,
+                    _ => TokenKind::Ident(word),
+                };
+                (kind, len)
+            }
+            (c, _) => {
+                return Err(ExprError::new(
+                    column,
+                    format!("unexpected character '{}'", c),
+                ));
+            }
+        };
+
+        tokens.push(Token { kind, column });
+        i += len;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+fn lex_string(chars: &[char], start: usize) -> Result<(String, usize), ExprError> {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                let escaped = match chars.get(i + 1) {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some(&c @ ('\\' | '"' | '\'')) => c,
+                    Some(c) => {
+                        return Err(ExprError::new(i + 1, format!("unknown escape '\\{}'", c)));
+                    }
+                    None => break,
+                };
+                value.push(escaped);
+                i += 2;
+            }
+            c if c == quote => return Ok((value, i + 1 - start)),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err(ExprError::new(start + 1, "unterminated string literal"))
+}
+
+fn lex_number_len(chars: &[char], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut seen_dot = false;
+    while let Some(&c) = chars.get(i) {
+        if c.is_ascii_digit() {
+            i += 1;
+        } else if c == '.' && !seen_dot && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()) {
+            seen_dot = true;
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    i - start
+}
+
+#[derive(Debug, Clone)]
+enum Literal {
+    Str(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone)]
+enum Atom {
+    Field(String),
+    Literal(Literal),
+}
+
+#[derive(Debug, Clone)]
+enum Ast {
+    /// A whole `||` or `&&` chain, so that long chains do not nest.
+    Or(Vec<Ast>),
+    And(Vec<Ast>),
+    Not(Box<Ast>),
+    Compare {
+        op: CmpOp,
+        op_column: usize,
+        left: (Atom, usize),
+        right: (Atom, usize),
+    },
+    Atom(Atom, usize),
+}
+
+/// Deepest nesting of `!` and parentheses accepted, so that the recursive descent cannot
+/// overflow the stack.
+const MAX_NESTING: usize = 256;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Ast, ExprError> {
+        let first = self.parse_and()?;
+        if self.peek().kind != TokenKind::Or {
+            return Ok(first);
+        }
+        let mut terms = vec![first];
+        while self.peek().kind == TokenKind::Or {
+            self.advance();
+            terms.push(self.parse_and()?);
+        }
+        Ok(Ast::Or(terms))
+    }
+
+    fn parse_and(&mut self) -> Result<Ast, ExprError> {
+        let first = self.parse_unary()?;
+        if self.peek().kind != TokenKind::And {
+            return Ok(first);
+        }
+        let mut terms = vec![first];
+        while self.peek().kind == TokenKind::And {
+            self.advance();
+            terms.push(self.parse_unary()?);
+        }
+        Ok(Ast::And(terms))
+    }
+
+    fn parse_unary(&mut self) -> Result<Ast, ExprError> {
+        match self.peek().kind {
+            TokenKind::Not => {
+                self.enter()?;
+                self.advance();
+                let operand = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(Ast::Not(Box::new(operand)))
+            }
+            TokenKind::LParen => {
+                self.enter()?;
+                let open = self.advance();
+                let inner = self.parse_or()?;
+                let close = self.advance();
+                if close.kind != TokenKind::RParen {
+                    return Err(ExprError::new(
+                        close.column,
+                        format!("expected ')' to close '(' at column {}", open.column),
+                    ));
+                }
+                self.depth -= 1;
+                Ok(inner)
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ExprError> {
+        if self.depth == MAX_NESTING {
+            return Err(ExprError::new(
+                self.peek().column,
+                format!("expression nested more than {} levels deep", MAX_NESTING),
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn parse_comparison(&mut self) -> Result<Ast, ExprError> {
+        let left = self.parse_atom()?;
+        let TokenKind::Cmp(op) = self.peek().kind else {
+            return Ok(Ast::Atom(left.0, left.1));
+        };
+        let op_column = self.advance().column;
+        let right = self.parse_atom()?;
+        Ok(Ast::Compare {
+            op,
+            op_column,
+            left,
+            right,
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<(Atom, usize), ExprError> {
+        let token = self.advance();
+        let atom = match token.kind {
+            TokenKind::Ident(path) => Atom::Field(path),
+            TokenKind::Str(s) => Atom::Literal(Literal::Str(s)),
+            TokenKind::Number(n) => Atom::Literal(Literal::Number(n)),
+            TokenKind::True => Atom::Literal(Literal::Bool(true)),
+            TokenKind::False => Atom::Literal(Literal::Bool(false)),
+            TokenKind::Null => Atom::Literal(Literal::Null),
+            TokenKind::Eof => {
+                return Err(ExprError::new(token.column, "unexpected end of expression"));
+            }
+            _ => {
+                return Err(ExprError::new(
+                    token.column,
+                    "expected a field, string, number, true, false or null",
+                ));
+            }
+        };
+        Ok((atom, token.column))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Field(Field),
+    Value(FieldValue<'static>),
+}
+
+impl Operand {
+    fn resolve<'a>(&'a self, event: &'a GitHubEvent) -> FieldValue<'a> {
+        match self {
+            Operand::Field(field) => field.read(event),
+            Operand::Value(value) => value.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Or(Vec<Node>),
+    And(Vec<Node>),
+    Not(Box<Node>),
+    Compare {
+        op: CmpOp,
+        left: Operand,
+        right: Operand,
+    },
+    Matches {
+        operand: Operand,
+        regex: Regex,
+        negated: bool,
+    },
+    Truthy(Operand),
+}
+
+/// Operand type as seen by the checker. `None` stands for the `null` literal.
+type OperandType = Option<FieldType>;
+
+fn check_atom(atom: Atom, column: usize) -> Result<(Operand, OperandType), ExprError> {
+    match atom {
+        Atom::Field(path) => match Field::resolve(&path) {
+            Some(field) => {
+                let field_type = field.field_type();
+                Ok((Operand::Field(field), Some(field_type)))
+            }
+            None => Err(ExprError::new(column, unknown_field_message(&path))),
+        },
+        Atom::Literal(Literal::Str(s)) => Ok((
+            Operand::Value(FieldValue::Str(Cow::Owned(s))),
+            Some(FieldType::String),
+        )),
+        Atom::Literal(Literal::Number(n)) => Ok((
+            Operand::Value(FieldValue::Number(n)),
+            Some(FieldType::Number),
+        )),
+        Atom::Literal(Literal::Bool(b)) => {
+            Ok((Operand::Value(FieldValue::Bool(b)), Some(FieldType::Bool)))
+        }
+        Atom::Literal(Literal::Null) => Ok((Operand::Value(FieldValue::Null), None)),
+    }
+}
+
+fn unknown_field_message(path: &str) -> String {
+    let parent = path.rsplit_once('.').map(|(parent, _)| parent);
+    let siblings: Vec<&str> = fields::known_fields()
+        .filter(|name| name.rsplit_once('.').map(|(p, _)| p) == parent)
+        .collect();
+
+    if siblings.is_empty() {
+        format!("unknown field '{}'", path)
+    } else {
+        format!(
+            "unknown field '{}' (known fields here: {})",
+            path,
+            siblings.join(", ")
+        )
+    }
+}
+
+/// Lets string literals stand for timestamps, e.g. `created_at >= "2024-01-01T00:00:00Z"`.
+fn coerce_timestamp(
+    operand: Operand,
+    operand_type: OperandType,
+    other_type: OperandType,
+    column: usize,
+) -> Result<(Operand, OperandType), ExprError> {
+    match (&operand, operand_type, other_type) {
+        (
+            Operand::Value(FieldValue::Str(text)),
+            Some(FieldType::String),
+            Some(FieldType::Timestamp),
+        ) => {
+            let time = DateTime::parse_from_rfc3339(text).map_err(|e| {
+                ExprError::new(
+                    column,
+                    format!("invalid RFC 3339 timestamp '{}': {}", text, e),
+                )
+            })?;
+            Ok((
+                Operand::Value(FieldValue::Timestamp(time.with_timezone(&Utc))),
+                Some(FieldType::Timestamp),
+            ))
+        }
+        _ => Ok((operand, operand_type)),
+    }
+}
+
+fn type_name(operand_type: OperandType) -> &'static str {
+    operand_type.map_or("null", |t| t.name())
+}
+
+fn check(ast: Ast) -> Result<Node, ExprError> {
+    match ast {
+        Ast::Or(terms) => Ok(Node::Or(
+            terms.into_iter().map(check).collect::<Result<_, _>>()?,
+        )),
+        Ast::And(terms) => Ok(Node::And(
+            terms.into_iter().map(check).collect::<Result<_, _>>()?,
+        )),
+        Ast::Not(inner) => Ok(Node::Not(Box::new(check(*inner)?))),
+        Ast::Atom(atom, column) => {
+            let (operand, operand_type) = check_atom(atom, column)?;
+            match operand_type {
+                Some(FieldType::Bool | FieldType::Any) => Ok(Node::Truthy(operand)),
+                other => Err(ExprError::new(
+                    column,
+                    format!(
+                        "expected a boolean condition, found a {} value",
+                        type_name(other)
+                    ),
+                )),
+            }
+        }
+        Ast::Compare {
+            op: op @ (CmpOp::Match | CmpOp::NotMatch),
+            op_column,
+            left,
+            right,
+        } => {
+            let (operand, operand_type) = check_atom(left.0, left.1)?;
+            if !matches!(operand_type, Some(FieldType::String | FieldType::Any)) {
+                return Err(ExprError::new(
+                    op_column,
+                    format!(
+                        "'{}' needs a string on the left, found {}",
+                        op.symbol(),
+                        type_name(operand_type)
+                    ),
+                ));
+            }
+            let Atom::Literal(Literal::Str(pattern)) = right.0 else {
+                return Err(ExprError::new(
+                    right.1,
+                    format!(
+                        "'{}' needs a regex string literal on the right",
+                        op.symbol()
+                    ),
+                ));
+            };
+            let regex = Regex::new(&pattern)
+                .map_err(|e| ExprError::new(right.1, format!("invalid regex: {}", e)))?;
+            Ok(Node::Matches {
+                operand,
+                regex,
+                negated: op == CmpOp::NotMatch,
+            })
+        }
+        Ast::Compare {
+            op,
+            op_column,
+            left,
+            right,
+        } => {
+            let (left_operand, left_type) = check_atom(left.0, left.1)?;
+            let (right_operand, right_type) = check_atom(right.0, right.1)?;
+            let (left_operand, left_type) =
+                coerce_timestamp(left_operand, left_type, right_type, left.1)?;
+            let (right_operand, right_type) =
+                coerce_timestamp(right_operand, right_type, left_type, right.1)?;
+
+            let mismatch = || {
+                ExprError::new(
+                    op_column,
+                    format!(
+                        "cannot compare {} with {} using '{}'",
+                        type_name(left_type),
+                        type_name(right_type),
+                        op.symbol()
+                    ),
+                )
+            };
+
+            let compatible = match (left_type, right_type) {
+                (None, _) | (_, None) => !op.is_ordering(),
+                (Some(FieldType::Any), _) | (_, Some(FieldType::Any)) => true,
+                (Some(FieldType::Bool), Some(FieldType::Bool)) => !op.is_ordering(),
+                (Some(a), Some(b)) => a == b,
+            };
+            if !compatible {
+                return Err(mismatch());
+            }
+
+            Ok(Node::Compare {
+                op,
+                left: left_operand,
+                right: right_operand,
+            })
+        }
+    }
+}
+
+fn eval(node: &Node, event: &GitHubEvent) -> bool {
+    match node {
+        Node::Or(terms) => terms.iter().any(|term| eval(term, event)),
+        Node::And(terms) => terms.iter().all(|term| eval(term, event)),
+        Node::Not(inner) => !eval(inner, event),
+        Node::Truthy(operand) => operand.resolve(event) == FieldValue::Bool(true),
+        Node::Matches {
+            operand,
+            regex,
+            negated,
+        } => {
+            let is_match = match operand.resolve(event) {
+                FieldValue::Str(s) => regex.is_match(&s),
+                _ => false,
+            };
+            is_match != *negated
+        }
+        Node::Compare { op, left, right } => {
+            let ordering = left.resolve(event).compare(&right.resolve(event));
+            match op {
+                CmpOp::Eq => ordering == Some(Ordering::Equal),
+                CmpOp::Ne => ordering != Some(Ordering::Equal),
+                CmpOp::Lt => ordering == Some(Ordering::Less),
+                CmpOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                CmpOp::Gt => ordering == Some(Ordering::Greater),
+                CmpOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                CmpOp::Match | CmpOp::NotMatch => false,
+            }
+        }
+    }
+}
+
+/// A parsed and type-checked `--where` expression.
+///
+/// Supports `&&`, `||`, `!`, parentheses, `== != < <= > >=` and regex matching with
+/// `~` / `!~` over dotted field paths (`repo.name`, `payload.action`, ...), string,
+/// number, `true`, `false` and `null` literals. Values of different types never compare equal.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    root: Node,
+}
+
+impl Predicate {
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            depth: 0,
+        };
+        let ast = parser.parse_or()?;
+
+        let trailing = parser.peek();
+        if trailing.kind != TokenKind::Eof {
+            return Err(ExprError::new(
+                trailing.column,
+                "unexpected token, expected '&&', '||' or end of expression",
+            ));
+        }
+
+        Ok(Predicate { root: check(ast)? })
+    }
+
+    pub fn matches(&self, event: &GitHubEvent) -> bool {
+        eval(&self.root, event)
+    }
+}
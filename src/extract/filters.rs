@@ -1,7 +1,10 @@
-use crate::model::github::{EventType, GitHubEvent};
-use std::fs::OpenOptions;
-use std::io::BufWriter;
-use std::io::Write;
+use crate::extract::expr::Predicate;
+use crate::extract::sink::{self, EventSink, JsonLinesSink};
+use crate::model::github::GitHubEvent;
+use crate::{Config, EtlError};
+use chrono::{DateTime, Duration, Utc};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
 
 pub fn is_valid_event_type(event_type_str: &str) -> bool {
     matches!(
@@ -25,53 +28,228 @@ pub fn is_valid_event_type(event_type_str: &str) -> bool {
     )
 }
 
-fn event_type_to_str(event_type: &EventType) -> &'static str {
-    match event_type {
-        EventType::PushEvent => "PushEvent",
-        EventType::PullRequestEvent => "PullRequestEvent",
-        EventType::PullRequestReviewEvent => "PullRequestReviewEvent",
-        EventType::PullRequestReviewCommentEvent => "PullRequestReviewCommentEvent",
-        EventType::CreateEvent => "CreateEvent",
-        EventType::DeleteEvent => "DeleteEvent",
-        EventType::IssuesEvent => "IssuesEvent",
-        EventType::IssueCommentEvent => "IssueCommentEvent",
-        EventType::WatchEvent => "WatchEvent",
-        EventType::ForkEvent => "ForkEvent",
-        EventType::ReleaseEvent => "ReleaseEvent",
-        EventType::GollumEvent => "GollumEvent",
-        EventType::MemberEvent => "MemberEvent",
-        EventType::PublicEvent => "PublicEvent",
-        EventType::CommitCommentEvent => "CommitCommentEvent",
-        EventType::DiscussionEvent => "DiscussionEvent",
-    }
+/// Shape of a GitHub event type name (`SponsorshipEvent`), used to accept types
+/// that appeared upstream after this model was written.
+pub fn is_event_type_name(event_type_str: &str) -> bool {
+    event_type_str.strip_suffix("Event").is_some_and(|name| {
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name.chars().all(|c| c.is_ascii_alphanumeric())
+    })
 }
 
 pub fn matches_actor_filter(actor_login: &str, filter: &str) -> bool {
     actor_login.eq_ignore_ascii_case(filter)
 }
 
-pub fn should_include(event: &GitHubEvent, filter: &Option<String>) -> bool {
-    match filter {
-        None => true,
-        Some(filter_str) => event_type_to_str(&event.event_type) == filter_str.as_str(),
+/// Pattern for repository and organization names.
+///
+/// `re:<regex>` is a regular expression, a pattern containing `*`, `?` or `[` is a glob
+/// where `*` does not cross `/` (so `rust-lang/*` matches every repo of the org),
+/// anything else is an exact match. Exact and glob matches ignore ASCII case, like GitHub does.
+#[derive(Debug, Clone)]
+pub enum NamePattern {
+    Exact(String),
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl NamePattern {
+    pub fn parse(pattern: &str) -> Result<Self, EtlError> {
+        if let Some(expr) = pattern.strip_prefix("re:") {
+            return Regex::new(expr).map(NamePattern::Regex).map_err(|e| {
+                EtlError::invalid_filter_caused_by(
+                    format!("Invalid regex pattern '{}': {}", expr, e),
+                    e,
+                )
+            });
+        }
+
+        if pattern.contains(['*', '?', '[']) {
+            return GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .literal_separator(true)
+                .build()
+                .map(|glob| NamePattern::Glob(glob.compile_matcher()))
+                .map_err(|e| {
+                    EtlError::invalid_filter_caused_by(
+                        format!("Invalid glob pattern '{}': {}", pattern, e),
+                        e,
+                    )
+                });
+        }
+
+        if pattern.is_empty() {
+            return Err(EtlError::invalid_filter("Name pattern must not be empty"));
+        }
+
+        Ok(NamePattern::Exact(pattern.to_string()))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NamePattern::Exact(expected) => name.eq_ignore_ascii_case(expected),
+            NamePattern::Glob(matcher) => matcher.is_match(name),
+            NamePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// Criteria an event must satisfy to be kept. Empty criteria match everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub event_types: Vec<String>,
+    pub exclude_event_types: Vec<String>,
+    pub actors: Vec<String>,
+    pub repos: Vec<NamePattern>,
+    pub orgs: Vec<NamePattern>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub predicate: Option<Predicate>,
+}
+
+impl EventFilter {
+    pub fn from_config(config: &Config) -> Result<Self, EtlError> {
+        let now = Utc::now();
+        Ok(EventFilter {
+            event_types: validate_event_types(&config.event_type_filter)?,
+            exclude_event_types: validate_event_types(&config.exclude_event_type_filter)?,
+            actors: config.actor_filter.clone(),
+            repos: parse_patterns(&config.repo_filter)?,
+            orgs: parse_patterns(&config.org_filter)?,
+            since: config
+                .since
+                .as_deref()
+                .map(|value| parse_time_bound(value, now))
+                .transpose()?,
+            until: config
+                .until
+                .as_deref()
+                .map(|value| parse_time_bound(value, now))
+                .transpose()?,
+            predicate: config
+                .where_clause
+                .as_deref()
+                .map(|source| {
+                    Predicate::parse(source).map_err(|e| {
+                        EtlError::invalid_filter_caused_by(
+                            format!("Invalid --where expression at {}", e.render(source)),
+                            e,
+                        )
+                    })
+                })
+                .transpose()?,
+        })
+    }
+}
+
+/// Parses an RFC 3339 timestamp (`2024-01-01T15:00:00Z`) or a time relative to `now`
+/// such as `-6h`, `-30m`, `-2d` or `-1w`.
+pub fn parse_time_bound(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, EtlError> {
+    if let Some(relative) = value.strip_prefix('-') {
+        let invalid = || {
+            EtlError::invalid_filter(format!(
+                "Invalid relative time '{}': expected a number followed by s, m, h, d or w (e.g. -6h)",
+                value
+            ))
+        };
+        let (unit_at, _) = relative.char_indices().next_back().ok_or_else(invalid)?;
+        let (amount, unit) = relative.split_at(unit_at);
+        let amount: i64 = amount.parse().map_err(|_| invalid())?;
+        let offset = match unit {
+            "s" => Duration::try_seconds(amount),
+            "m" => Duration::try_minutes(amount),
+            "h" => Duration::try_hours(amount),
+            "d" => Duration::try_days(amount),
+            "w" => Duration::try_weeks(amount),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+        return now.checked_sub_signed(offset).ok_or_else(invalid);
     }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| {
+            EtlError::invalid_filter_caused_by(format!("Invalid timestamp '{}': {}", value, e), e)
+        })
+}
+
+/// `since` is inclusive and `until` exclusive.
+pub fn matches_time_window(
+    created_at: DateTime<Utc>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> bool {
+    since.is_none_or(|since| created_at >= since) && until.is_none_or(|until| created_at < until)
+}
+
+fn validate_event_types(event_types: &[String]) -> Result<Vec<String>, EtlError> {
+    for event_type in event_types {
+        if !is_valid_event_type(event_type) && !is_event_type_name(event_type) {
+            return Err(EtlError::invalid_filter(format!(
+                "Invalid event type: '{}'. Valid types are: PushEvent, PullRequestEvent, PullRequestReviewEvent, PullRequestReviewCommentEvent, CreateEvent, DeleteEvent, IssuesEvent, IssueCommentEvent, WatchEvent, ForkEvent, ReleaseEvent, GollumEvent, MemberEvent, PublicEvent, CommitCommentEvent, DiscussionEvent, or any other <Name>Event type",
+                event_type
+            )));
+        }
+    }
+    Ok(event_types.to_vec())
+}
+
+fn parse_patterns(patterns: &[String]) -> Result<Vec<NamePattern>, EtlError> {
+    patterns.iter().map(|p| NamePattern::parse(p)).collect()
 }
 
-pub fn save_events(events: &[GitHubEvent], output_path: &str) -> Result<(), String> {
-    let raw_file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(output_path)
-        .map_err(|e| format!("Failed to open output file: {}", e))?;
+pub fn matches_repo_filter(repo_name: &str, patterns: &[NamePattern]) -> bool {
+    patterns.is_empty() || patterns.iter().any(|p| p.matches(repo_name))
+}
 
-    let mut writer = BufWriter::new(raw_file);
+/// Events without an organization never match a non-empty org filter.
+pub fn matches_org_filter(org_login: Option<&str>, patterns: &[NamePattern]) -> bool {
+    patterns.is_empty() || org_login.is_some_and(|login| patterns.iter().any(|p| p.matches(login)))
+}
 
-    for event in events {
-        let json_line = serde_json::to_string(event)
-            .map_err(|e| format!("Failed to serialize event: {}", e))?;
+pub fn should_include(event: &GitHubEvent, filter: &EventFilter) -> bool {
+    let event_type = event.event_type.as_str();
+    if !filter.event_types.is_empty() && !filter.event_types.iter().any(|t| t == event_type) {
+        return false;
+    }
 
-        writeln!(writer, "{}", json_line).map_err(|e| format!("Failed to write to file: {}", e))?;
+    if filter.exclude_event_types.iter().any(|t| t == event_type) {
+        return false;
     }
 
-    Ok(())
+    let actor_matches = filter.actors.is_empty()
+        || filter
+            .actors
+            .iter()
+            .any(|actor| matches_actor_filter(&event.actor.login, actor));
+
+    actor_matches
+        && matches_time_window(event.created_at, filter.since, filter.until)
+        && matches_repo_filter(&event.repo.name, &filter.repos)
+        && matches_org_filter(
+            event.org.as_ref().map(|org| org.login.as_str()),
+            &filter.orgs,
+        )
+        && filter
+            .predicate
+            .as_ref()
+            .is_none_or(|predicate| predicate.matches(event))
+}
+
+pub fn save_events(events: &[GitHubEvent], output_path: &str) -> Result<(), EtlError> {
+    let mut sink = JsonLinesSink::append(output_path)?;
+    sink.write_batch(events)?;
+    sink.finish()
+}
+
+/// Like [`save_events`], in the output format, columns and Parquet options of `config`.
+pub fn save_events_as(
+    events: &[GitHubEvent],
+    output_path: &str,
+    config: &Config,
+) -> Result<(), EtlError> {
+    let mut sink = sink::open_output(output_path, config)?;
+    sink.write_batch(events)?;
+    sink.finish()
 }
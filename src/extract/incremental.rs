@@ -0,0 +1,123 @@
+use crate::extract::checkpoint::{
+    AppendedFiles, OutputLengths, ProgressLog, STATE_VERSION, load_state, save_state,
+};
+use crate::extract::discovery::archive_hour;
+use crate::{Config, EtlError, OutputFormat};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Contents of an incremental state file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncrementalState {
+    pub version: u32,
+    /// Start of the latest hour processed.
+    pub high_water_mark: Option<DateTime<Utc>>,
+    /// Files at or before the mark that could not be processed, tried again by the next run.
+    #[serde(default)]
+    pub retry: Vec<String>,
+    #[serde(flatten)]
+    pub outputs: OutputLengths,
+}
+
+impl IncrementalState {
+    pub fn load(path: &Path) -> Result<Option<Self>, EtlError> {
+        load_state(path, |state: &IncrementalState| state.version)
+    }
+}
+
+/// Processes only the GH Archive hours newer than the high-water mark of the previous run,
+/// plus the files that run failed on, and appends them to its outputs. Files without an hour
+/// in their name are never selected, and a file arriving late for an hour at or before the
+/// mark is not picked up.
+pub struct Incremental {
+    path: PathBuf,
+    state: IncrementalState,
+    appended: AppendedFiles,
+    selected: Vec<PathBuf>,
+    done: HashSet<PathBuf>,
+    existed: bool,
+}
+
+impl Incremental {
+    pub fn load(state_path: &str, config: &Config) -> Result<Self, EtlError> {
+        if config.output_format == OutputFormat::Parquet && config.output_file.is_some() {
+            return Err(EtlError::Config(
+                "Parquet output cannot be appended to in incremental mode".to_string(),
+            ));
+        }
+        let path = PathBuf::from(state_path);
+        let appended = AppendedFiles::of(config);
+        let loaded = IncrementalState::load(&path)?;
+        let existed = loaded.is_some();
+        let state = match loaded {
+            Some(state) => state,
+            None => {
+                // The first run appends to whatever is there already.
+                let mut state = IncrementalState {
+                    version: STATE_VERSION,
+                    high_water_mark: None,
+                    retry: Vec::new(),
+                    outputs: OutputLengths::new(config),
+                };
+                appended.measure(&mut state.outputs)?;
+                state
+            }
+        };
+        state.outputs.check(config, &path)?;
+
+        Ok(Incremental {
+            path,
+            state,
+            appended,
+            selected: Vec::new(),
+            done: HashSet::new(),
+            existed,
+        })
+    }
+}
+
+impl ProgressLog for Incremental {
+    fn select(&mut self, mut files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mark = self.state.high_water_mark;
+        let retry: HashSet<&str> = self.state.retry.iter().map(String::as_str).collect();
+        files.retain(|path| {
+            retry.contains(path.display().to_string().as_str())
+                || archive_hour(path).is_some_and(|hour| mark.is_none_or(|mark| hour > mark))
+        });
+        self.selected.extend(files.iter().cloned());
+        files
+    }
+
+    /// Drops what a run that crashed after the last save appended.
+    fn rewind(&self) -> Result<(), EtlError> {
+        if self.existed {
+            self.appended.truncate_to(&self.state.outputs)?;
+        }
+        Ok(())
+    }
+
+    fn file_done(&mut self, path: &Path) {
+        self.done.insert(path.to_path_buf());
+    }
+
+    /// Moves the mark to the latest hour committed; every selected file not committed yet,
+    /// failed or still to come in this run, is kept for a retry.
+    fn save(&mut self) -> Result<(), EtlError> {
+        let latest = self.done.iter().filter_map(|path| archive_hour(path)).max();
+        self.state.high_water_mark = self.state.high_water_mark.max(latest);
+        self.state.retry = self
+            .selected
+            .iter()
+            .filter(|path| !self.done.contains(*path))
+            .map(|path| path.display().to_string())
+            .collect();
+        self.appended.measure(&mut self.state.outputs)?;
+        save_state(&self.path, &self.state)
+    }
+
+    fn discard_output(&self) -> Result<(), EtlError> {
+        self.appended.truncate_output_to(&self.state.outputs)
+    }
+}
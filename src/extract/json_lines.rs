@@ -1,12 +1,29 @@
-use crate::extract::analysis;
-use crate::extract::filters::{is_valid_event_type, save_events, should_include};
+use crate::extract::analysis::{self, EventCounter};
+use crate::extract::budget::ErrorBudget;
+use crate::extract::checkpoint::{self, Checkpoint, ProgressLog};
+use crate::extract::filters::{EventFilter, should_include};
+use crate::extract::incremental::Incremental;
+use crate::extract::sink::{self, EventSink, RejectSink, RejectedLine};
+use crate::extract::source::{InputSource, open_input};
+use crate::extract::watch::{InputWatcher, RollingRate};
 use crate::model::github::GitHubEvent;
+use crate::{Config, EtlError, OutputFormat, RunSummary};
 use rayon::prelude::*;
-use std::fs;
-use std::fs::File as StdFile;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::time::Instant;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of events a reader groups together before handing them to the sink in streaming mode.
+const STREAM_BATCH_SIZE: usize = 1_000;
+
+/// Number of lines of a file parsed in parallel at a time.
+const PARSE_BATCH_LINES: usize = 4_096;
+
+/// Window of the events-per-second rate a watching run reports.
+const WATCH_RATE_WINDOW: Duration = Duration::from_secs(60);
 
 pub fn check_folder(
     folder_path: &str,
@@ -15,93 +32,477 @@ pub fn check_folder(
     event_filter: Option<String>,
     output_file: Option<String>,
     quiet_mode: bool,
-) -> Result<(), String> {
-    if let Some(filter) = &event_filter {
-        if !is_valid_event_type(filter) {
-            return Err(format!(
-                "Invalid event type: '{}'. Valid types are: PushEvent, PullRequestEvent, PullRequestReviewEvent, PullRequestReviewCommentEvent, CreateEvent, DeleteEvent, IssuesEvent, IssueCommentEvent, WatchEvent, ForkEvent, ReleaseEvent, GollumEvent, MemberEvent, PublicEvent, CommitCommentEvent, DiscussionEvent",
-                filter
+) -> Result<RunSummary, EtlError> {
+    process(&Config {
+        path_to_data: folder_path.to_string(),
+        dry_run,
+        show_stats,
+        event_type_filter: event_filter.into_iter().collect(),
+        output_file,
+        quiet_mode,
+        ..Default::default()
+    })
+}
+
+pub fn process(config: &Config) -> Result<RunSummary, EtlError> {
+    let event_filter = EventFilter::from_config(config)?;
+    let budget = ErrorBudget::new(config.max_errors, config.max_error_rate, config.fail_fast)?;
+
+    if config.streaming && config.max_in_flight == 0 {
+        return Err(EtlError::Config(
+            "max_in_flight must be greater than zero".to_string(),
+        ));
+    }
+
+    let source = InputSource::from_config(config, event_filter.since, event_filter.until)?;
+    let mut progress = open_progress(config)?;
+    if matches!(source, InputSource::Stdin) && progress.is_some() {
+        return Err(EtlError::Config(
+            "The standard input cannot be checkpointed or processed incrementally".to_string(),
+        ));
+    }
+
+    let mut files = source.list()?;
+    let listed = files.len();
+    if let Some(progress) = progress.as_mut() {
+        files = progress.select(files);
+        if !config.quiet_mode && files.len() < listed {
+            println!(
+                "Skipping {} files already processed by earlier runs",
+                listed - files.len()
+            );
+        }
+    }
+
+    let summary = if config.dry_run {
+        execute_dry_run(&files, config)
+    } else if config.streaming {
+        execute_streaming_run(&files, &event_filter, &budget, progress, config)
+    } else {
+        execute_normal_run(&files, &event_filter, &budget, progress, config)
+    }?;
+    Ok(RunSummary {
+        skipped_files: listed - files.len(),
+        ..summary
+    })
+}
+
+/// Keeps processing the input files of the folder as they appear and stop changing, until
+/// `stop` is set. Outputs are appended to and made durable after every group of files; without
+/// a checkpoint or incremental state to continue from, they are emptied first.
+pub fn watch(config: &Config, stop: &AtomicBool) -> Result<RunSummary, EtlError> {
+    let event_filter = EventFilter::from_config(config)?;
+    let budget = ErrorBudget::new(config.max_errors, config.max_error_rate, config.fail_fast)?;
+
+    if config.dry_run {
+        return Err(EtlError::Config(
+            "Watch mode cannot be combined with a dry run".to_string(),
+        ));
+    }
+    if config.output_format == OutputFormat::Parquet && config.output_file.is_some() {
+        return Err(EtlError::Config(
+            "Parquet output cannot be appended to in watch mode".to_string(),
+        ));
+    }
+
+    let discovery = match InputSource::from_config(config, event_filter.since, event_filter.until)?
+    {
+        InputSource::Paths(discovery) => discovery,
+        InputSource::Stdin => {
+            return Err(EtlError::Config(
+                "The standard input cannot be watched".to_string(),
             ));
         }
+    };
+
+    let start_total = Instant::now();
+    let progress = open_progress(config)?;
+    if progress.is_none() {
+        checkpoint::start_over(config)?;
     }
+    let roots: Vec<String> = discovery
+        .roots
+        .iter()
+        .map(|root| root.display().to_string())
+        .collect();
+    let mut watcher = InputWatcher::new(discovery, config.watch_settle)?;
+    let mut output = RunOutput::open(config, progress)?;
+    let mut rate = RollingRate::new(WATCH_RATE_WINDOW);
+    let mut files = 0usize;
+    let mut skipped_files = 0usize;
 
-    // let start_total = Instant::now();
-    let entries = fs::read_dir(folder_path)
-        .map_err(|e| format!("Unable to read folder {}: {}", folder_path, e))?;
+    if !config.quiet_mode {
+        println!("Watching {} for input files", roots.join(", "));
+    }
+    loop {
+        let stable = watcher.next_stable(stop)?;
+        if stable.is_empty() {
+            break;
+        }
+        let listed = stable.len();
+        let group = match output.progress.as_mut() {
+            Some(progress) => progress.select(stable),
+            None => stable,
+        };
+        skipped_files += listed - group.len();
+        if group.is_empty() {
+            continue;
+        }
 
-    let mut files: Vec<PathBuf> = Vec::new();
-    for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-            files.push(path);
+        let events_before = output.counter.total();
+        let contents = read_files(&group, &event_filter, &budget, config.quiet_mode);
+        // The events of the file that blew the budget are dropped, earlier files stay committed.
+        let aborted = budget.should_abort();
+        for file in &contents {
+            for line in &file.rejected {
+                output.write_rejected(line)?;
+            }
+            if aborted {
+                continue;
+            }
+            output.write_events(&file.events)?;
+            if file.complete {
+                output.file_done(file.path);
+            }
+        }
+        if output.progress.is_some() {
+            output.save_progress()?;
+        } else {
+            output.sync_outputs()?;
+        }
+        files += group.len();
+        rate.record(output.counter.total() - events_before);
+
+        if !config.quiet_mode {
+            println!(
+                "[Watch] {} files, {} events ({:.1} events/s over the last minute), {} rejected lines",
+                files,
+                output.counter.total(),
+                rate.per_second(),
+                output.rejected
+            );
+        }
+        if aborted {
+            break;
         }
     }
 
-    files.sort_by_key(|path| {
-        path.file_stem()
-            .and_then(|s| s.to_str())
-            .and_then(|s| s.split('-').next_back())
-            .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(0)
-    });
+    if !config.quiet_mode && stop.load(Ordering::Relaxed) {
+        println!("Stopping, finishing outputs");
+    }
+    output.finish()?;
 
-    if let Some(output) = &output_file {
-        fs::write(output, "").map_err(|e| format!("Failed to create output file: {}", e))?;
+    let summary = RunSummary {
+        skipped_files,
+        ..output.summary(files, &budget)
+    };
+    if config.show_stats && summary.events > 0 {
+        analysis::print_stats(output.counter.counts());
     }
+    print_summary_normal_run(&summary, &budget, config, start_total);
 
-    // let total_files = files.len();
+    Ok(summary)
+}
 
-    if dry_run {
-        execute_dry_run(&files, &event_filter, quiet_mode)
-    } else {
-        execute_normal_run(&files, &event_filter, show_stats, &output_file, quiet_mode)
+/// The checkpoint or incremental state the run keeps, if any.
+fn open_progress(config: &Config) -> Result<Option<Box<dyn ProgressLog>>, EtlError> {
+    if config.resume && config.checkpoint_file.is_none() {
+        return Err(EtlError::Config(
+            "Resuming needs the checkpoint file of the interrupted run".to_string(),
+        ));
+    }
+    match (&config.checkpoint_file, &config.incremental_state) {
+        (Some(_), Some(_)) => Err(EtlError::Config(
+            "A run is either checkpointed or incremental, not both".to_string(),
+        )),
+        (Some(path), None) => Ok(Some(Box::new(Checkpoint::load(path, config)?))),
+        (None, Some(path)) => Ok(Some(Box::new(Incremental::load(path, config)?))),
+        (None, None) => Ok(None),
     }
 }
 
 pub fn receive_all(
     file_path: &str,
     event_filter: Option<String>,
-) -> Result<Vec<GitHubEvent>, String> {
-    let file = StdFile::open(file_path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
+) -> Result<Vec<GitHubEvent>, EtlError> {
+    let filter = EventFilter {
+        event_types: event_filter.into_iter().collect(),
+        ..Default::default()
+    };
     let mut results = Vec::new();
+    for_each_event(Path::new(file_path), &filter, |event| {
+        results.push(event);
+        Ok(())
+    })?;
+    Ok(results)
+}
 
-    for (index, line) in reader.lines().enumerate() {
-        let line = line.map_err(|e| e.to_string())?;
-        if line.trim().is_empty() {
-            continue;
+/// A non-empty input line: an event that passed the filter, or a line that failed to parse.
+#[allow(clippy::large_enum_variant)]
+pub enum ParsedLine {
+    Event(GitHubEvent),
+    Rejected(RejectedLine),
+}
+
+/// Parses a file and hands every event passing the filter, and every unparseable line, to
+/// `handle` in file order. Lines are read in batches that are parsed in parallel while the
+/// next batch is read, so no more than two batches are held in memory.
+/// Stops at the first error `handle` returns. Returns the number of non-empty lines read.
+pub fn for_each_line<F>(
+    path: &Path,
+    event_filter: &EventFilter,
+    mut handle: F,
+) -> Result<usize, EtlError>
+where
+    F: FnMut(ParsedLine) -> Result<(), EtlError>,
+{
+    let reader =
+        open_input(path).map_err(|e| EtlError::io("Failed to open input file", path, e))?;
+    let mut lines = reader.lines().enumerate();
+    let mut lines_read = 0usize;
+
+    let (mut batch, mut read_error) = read_batch(&mut lines);
+    loop {
+        let last = read_error.is_some() || batch.len() < PARSE_BATCH_LINES;
+        lines_read += batch.len();
+        let (parsed, next) = if last {
+            (parse_batch(path, batch, event_filter), (Vec::new(), None))
+        } else {
+            rayon::join(
+                || parse_batch(path, batch, event_filter),
+                || read_batch(&mut lines),
+            )
+        };
+
+        for line in parsed.into_iter().flatten() {
+            match line {
+                Ok(event) => handle(ParsedLine::Event(event))?,
+                Err((rejected, warning)) => {
+                    eprintln!("Warning: {}", warning);
+                    handle(ParsedLine::Rejected(rejected))?;
+                }
+            }
         }
+        if let Some(e) = read_error {
+            return Err(EtlError::io("Failed to read input file", path, e));
+        }
+        if last {
+            return Ok(lines_read);
+        }
+        (batch, read_error) = next;
+    }
+}
 
-        match serde_json::from_str::<GitHubEvent>(&line) {
-            Ok(event) => {
-                if should_include(&event, &event_filter) {
-                    results.push(event);
+/// Reads up to [`PARSE_BATCH_LINES`] non-empty lines with their 1-based line numbers,
+/// stopping early at the end of the input or at a read error.
+fn read_batch(
+    lines: &mut impl Iterator<Item = (usize, io::Result<String>)>,
+) -> (Vec<(usize, String)>, Option<io::Error>) {
+    let mut batch = Vec::with_capacity(PARSE_BATCH_LINES);
+    for (index, line) in lines {
+        match line {
+            Ok(line) if line.trim().is_empty() => {}
+            Ok(line) => {
+                batch.push((index + 1, line));
+                if batch.len() == PARSE_BATCH_LINES {
+                    break;
                 }
             }
-            Err(err) => {
-                eprintln!("Warning at line {}: {}", index + 1, err);
+            Err(e) => return (batch, Some(e)),
+        }
+    }
+    (batch, None)
+}
+
+/// A parsed line: `None` if its event was filtered out, the rejected line and its warning if
+/// it could not be parsed.
+type BatchLine = Option<Result<GitHubEvent, (RejectedLine, EtlError)>>;
+
+/// Parses a batch on the rayon pool, keeping the order of the lines.
+fn parse_batch(
+    path: &Path,
+    batch: Vec<(usize, String)>,
+    event_filter: &EventFilter,
+) -> Vec<BatchLine> {
+    batch
+        .into_par_iter()
+        .map_init(Vec::new, |scratch, (line_number, line)| {
+            match parse_event(&line, scratch) {
+                Ok(event) => should_include(&event, event_filter).then_some(Ok(event)),
+                Err(err) => Some(Err((
+                    RejectedLine {
+                        source: path.display().to_string(),
+                        line: line_number,
+                        error: err.to_string(),
+                        raw: line,
+                    },
+                    EtlError::parse(path, line_number, err),
+                ))),
             }
+        })
+        .collect()
+}
+
+/// Parses a line with serde_json.
+#[cfg(not(feature = "simd"))]
+fn parse_event(line: &str, _scratch: &mut Vec<u8>) -> Result<GitHubEvent, serde_json::Error> {
+    serde_json::from_str(line)
+}
+
+/// Parses a line with simd-json, which unescapes strings in place and so works on a copy of
+/// the line in `scratch`. A line simd-json rejects is parsed again with serde_json, which
+/// decides whether it is valid and reports where it is not.
+#[cfg(feature = "simd")]
+fn parse_event(line: &str, scratch: &mut Vec<u8>) -> Result<GitHubEvent, serde_json::Error> {
+    scratch.clear();
+    scratch.extend_from_slice(line.as_bytes());
+    simd_json::serde::from_slice(scratch).or_else(|_| serde_json::from_str(line))
+}
+
+/// Like [`for_each_line`], skipping rejected lines.
+pub fn for_each_event<F>(
+    path: &Path,
+    event_filter: &EventFilter,
+    mut handle: F,
+) -> Result<usize, EtlError>
+where
+    F: FnMut(GitHubEvent) -> Result<(), EtlError>,
+{
+    for_each_line(path, event_filter, |parsed| match parsed {
+        ParsedLine::Event(event) => handle(event),
+        ParsedLine::Rejected(_) => Ok(()),
+    })
+}
+
+/// Everything a run writes to: stats counter, output file, reject file and progress log.
+struct RunOutput {
+    counter: EventCounter,
+    output_sink: Option<Box<dyn EventSink>>,
+    reject_sink: Option<RejectSink>,
+    rejected: usize,
+    progress: Option<Box<dyn ProgressLog>>,
+}
+
+impl RunOutput {
+    fn open(config: &Config, progress: Option<Box<dyn ProgressLog>>) -> Result<Self, EtlError> {
+        if let Some(progress) = &progress {
+            progress.rewind()?;
+        }
+        let open_reject = if sink::appends(config) {
+            RejectSink::append
+        } else {
+            RejectSink::create
+        };
+        Ok(RunOutput {
+            counter: EventCounter::new(),
+            output_sink: config
+                .output_file
+                .as_deref()
+                .map(|path| sink::open_output(path, config))
+                .transpose()?,
+            reject_sink: config.reject_file.as_deref().map(open_reject).transpose()?,
+            rejected: 0,
+            progress,
+        })
+    }
+
+    fn write_events(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        self.counter.write_batch(events)?;
+        if let Some(sink) = self.output_sink.as_mut() {
+            sink.write_batch(events)?;
         }
+        Ok(())
+    }
+
+    fn write_rejected(&mut self, rejected: &RejectedLine) -> Result<(), EtlError> {
+        self.rejected += 1;
+        match self.reject_sink.as_mut() {
+            Some(sink) => sink.write(rejected),
+            None => Ok(()),
+        }
+    }
+
+    /// Drops the output without finishing it, which leaves the previous output in place.
+    /// An output appended to is cut back to the last saved progress, and no more is recorded.
+    fn discard_output(&mut self) -> Result<(), EtlError> {
+        self.output_sink = None;
+        match self.progress.take() {
+            Some(progress) => progress.discard_output(),
+            None => Ok(()),
+        }
+    }
+
+    fn file_done(&mut self, path: &Path) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.file_done(path);
+        }
+    }
+
+    /// Makes the outputs durable and records the files finished since the last save.
+    fn save_progress(&mut self) -> Result<(), EtlError> {
+        if self.progress.is_none() {
+            return Ok(());
+        }
+        self.sync_outputs()?;
+        match self.progress.as_mut() {
+            Some(progress) => progress.save(),
+            None => Ok(()),
+        }
+    }
+
+    /// Makes what was written to the outputs so far durable.
+    fn sync_outputs(&mut self) -> Result<(), EtlError> {
+        if let Some(sink) = self.output_sink.as_mut() {
+            sink.checkpoint()?;
+        }
+        if let Some(sink) = self.reject_sink.as_mut() {
+            sink.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    fn summary(&self, files: usize, budget: &ErrorBudget) -> RunSummary {
+        RunSummary {
+            files,
+            skipped_files: 0,
+            failed_files: budget.failed_files(),
+            lines: budget.lines(),
+            events: self.counter.total(),
+            rejected: self.rejected,
+            error_budget_exceeded: budget.is_exceeded(),
+            aborted: budget.should_abort(),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), EtlError> {
+        if let Some(sink) = self.output_sink.as_mut() {
+            sink.finish()?;
+        }
+        if let Some(sink) = self.reject_sink.as_mut() {
+            sink.finish()?;
+        }
+        Ok(())
     }
-    Ok(results)
 }
 
-fn execute_dry_run(
-    files: &[PathBuf],
-    event_filter: &Option<String>,
-    quiet_mode: bool,
-) -> Result<(), String> {
+enum StreamItem {
+    Events(Vec<GitHubEvent>),
+    Rejected(RejectedLine),
+    /// Everything read from the file was sent.
+    Done(PathBuf),
+}
+
+fn execute_dry_run(files: &[PathBuf], config: &Config) -> Result<RunSummary, EtlError> {
     let start = Instant::now();
     let mut total_lines = 0usize;
 
     for path in files {
-        let line_count = fs::read_to_string(path)
-            .map(|s| s.lines().count())
+        let line_count = open_input(path)
+            .map(|reader| reader.lines().map_while(Result::ok).count())
             .unwrap_or(0);
         total_lines += line_count;
 
-        if !quiet_mode {
+        if !config.quiet_mode {
             println!(
                 "[Dry-run]: Would process file: {:?}, {} lines",
                 path.file_name().unwrap_or_default(),
@@ -110,107 +511,348 @@ fn execute_dry_run(
         }
     }
 
-    print_summary_dry_run(files.len(), total_lines, event_filter, start, quiet_mode);
-    Ok(())
+    print_summary_dry_run(files.len(), total_lines, config, start);
+    Ok(RunSummary {
+        files: files.len(),
+        lines: total_lines,
+        ..Default::default()
+    })
 }
 
 fn execute_normal_run(
     files: &[PathBuf],
-    event_filter: &Option<String>,
-    show_stats: bool,
-    output_file: &Option<String>,
-    quiet_mode: bool,
-) -> Result<(), String> {
+    event_filter: &EventFilter,
+    budget: &ErrorBudget,
+    progress: Option<Box<dyn ProgressLog>>,
+    config: &Config,
+) -> Result<RunSummary, EtlError> {
     let start_total = Instant::now();
-    let total_files = files.len();
 
-    let all_events: Vec<GitHubEvent> = files
+    let mut output = RunOutput::open(config, progress)?;
+
+    // A run keeping a progress log saves it after every group of files read in parallel,
+    // otherwise all files are read before anything is written.
+    let group_size = match output.progress {
+        Some(_) => rayon::current_num_threads(),
+        None => files.len(),
+    };
+    for group in files.chunks(group_size.max(1)) {
+        let contents = read_files(group, event_filter, budget, config.quiet_mode);
+
+        // An aborted run still records the lines it rejected, but its events are not trusted
+        // and the previous output is kept.
+        let aborted = budget.should_abort();
+        if aborted {
+            output.discard_output()?;
+        }
+        for file in &contents {
+            for line in &file.rejected {
+                output.write_rejected(line)?;
+            }
+            if aborted {
+                continue;
+            }
+            if let Err(e) = output.write_events(&file.events) {
+                eprintln!(
+                    "Warning: Failed to save events to {}, keeping the previous output: {}",
+                    config.output_file.as_deref().unwrap_or_default(),
+                    e
+                );
+                output.discard_output()?;
+            }
+            if file.complete {
+                output.file_done(file.path);
+            }
+        }
+        output.save_progress()?;
+        if aborted {
+            break;
+        }
+    }
+    output.finish()?;
+
+    let summary = output.summary(files.len(), budget);
+
+    if config.show_stats && summary.events > 0 {
+        analysis::print_stats(output.counter.counts());
+    }
+
+    print_summary_normal_run(&summary, budget, config, start_total);
+
+    Ok(summary)
+}
+
+/// What the normal run read from one file.
+struct FileContents<'a> {
+    path: &'a Path,
+    events: Vec<GitHubEvent>,
+    rejected: Vec<RejectedLine>,
+    /// False when reading stopped early because the error budget was exceeded.
+    complete: bool,
+}
+
+/// Reads files in parallel, keeping their order. Unreadable files are reported and left out.
+fn read_files<'a>(
+    files: &'a [PathBuf],
+    event_filter: &EventFilter,
+    budget: &ErrorBudget,
+    quiet_mode: bool,
+) -> Vec<FileContents<'a>> {
+    files
         .par_iter()
         .filter_map(|path| {
+            if budget.should_abort() {
+                return None;
+            }
             let file_name = path.file_name().unwrap_or_default();
 
             if !quiet_mode {
                 println!("File processing: {:?}", file_name);
             }
 
-            let path_str = path.to_str()?;
-            match receive_all(path_str, event_filter.clone()) {
-                Ok(events) => {
+            let mut events = Vec::new();
+            let mut rejected = Vec::new();
+            let result = for_each_line(path, event_filter, |parsed| {
+                match parsed {
+                    ParsedLine::Event(event) => events.push(event),
+                    ParsedLine::Rejected(line) => {
+                        rejected.push(line);
+                        budget.record_error();
+                        if budget.should_abort() {
+                            return Err(budget.exceeded_error());
+                        }
+                    }
+                }
+                Ok(())
+            });
+            match result {
+                Ok(lines) => {
+                    budget.record_file(lines, rejected.len());
                     if !quiet_mode {
                         println!(" -> Success: {} events", events.len());
                     }
-                    Some(events)
+                    Some(FileContents {
+                        path,
+                        events,
+                        rejected,
+                        complete: true,
+                    })
+                }
+                Err(e @ EtlError::ErrorBudgetExceeded { .. }) => {
+                    eprintln!(" -> Aborted in file {:?}: {}", file_name, e);
+                    Some(FileContents {
+                        path,
+                        events: Vec::new(),
+                        rejected,
+                        complete: false,
+                    })
                 }
                 Err(e) => {
                     eprintln!(" -> Error in file {:?}: {}", file_name, e);
+                    budget.record_failed_file();
                     None
                 }
             }
         })
-        .flatten()
-        .collect();
+        .collect()
+}
 
-    let total_lines = all_events.len();
+/// Bounded-memory variant of the normal run: readers push batches through a bounded channel
+/// and a single consumer feeds the stats counter and the output sinks as batches arrive.
+/// Events of one file keep their order, but batches of different files may interleave.
+fn execute_streaming_run(
+    files: &[PathBuf],
+    event_filter: &EventFilter,
+    budget: &ErrorBudget,
+    progress: Option<Box<dyn ProgressLog>>,
+    config: &Config,
+) -> Result<RunSummary, EtlError> {
+    let start_total = Instant::now();
+    let quiet_mode = config.quiet_mode;
 
-    if show_stats && !all_events.is_empty() {
-        let stats = analysis::count_events(&all_events);
-        analysis::print_stats(&stats);
+    let batch_size = STREAM_BATCH_SIZE.min(config.max_in_flight);
+    let (sender, receiver) = mpsc::sync_channel(config.max_in_flight / batch_size);
+
+    // The progress log records output lengths whenever a file is done, so they must not
+    // include events of files still being read: a checkpointed or incremental run reads its
+    // files one at a time, the lines of each file still being parsed in parallel.
+    let one_file_at_a_time = progress.is_some();
+    let mut output = RunOutput::open(config, progress)?;
+
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let read = |sender: &mut mpsc::SyncSender<StreamItem>, path: &PathBuf| {
+                if !budget.should_abort() {
+                    stream_file(path, event_filter, budget, batch_size, sender, quiet_mode);
+                }
+            };
+            if one_file_at_a_time {
+                let mut sender = sender;
+                files.iter().for_each(|path| read(&mut sender, path));
+            } else {
+                files.par_iter().for_each_with(sender, read);
+            }
+        });
+
+        drain_stream(receiver, &mut output)
+    })?;
+    if budget.should_abort() {
+        output.discard_output()?;
     }
+    output.finish()?;
 
-    if let Some(output) = output_file {
-        if let Err(e) = save_events(&all_events, output) {
-            eprintln!("Warning: Failed to save events to {}: {}", output, e);
-        }
+    let summary = output.summary(files.len(), budget);
+
+    if config.show_stats && summary.events > 0 {
+        analysis::print_stats(output.counter.counts());
     }
 
-    print_summary_normal_run(
-        total_files,
-        total_lines,
-        event_filter,
-        output_file,
-        start_total,
-        quiet_mode,
-    );
+    print_summary_normal_run(&summary, budget, config, start_total);
 
+    Ok(summary)
+}
+
+fn stream_file(
+    path: &Path,
+    event_filter: &EventFilter,
+    budget: &ErrorBudget,
+    batch_size: usize,
+    sender: &mpsc::SyncSender<StreamItem>,
+    quiet_mode: bool,
+) {
+    let file_name = path.file_name().unwrap_or_default();
+
+    if !quiet_mode {
+        println!("File processing: {:?}", file_name);
+    }
+
+    // Events are held until the file has been read without error, so that a file failing
+    // partway writes nothing, as in a normal run, and can be retried without duplicates.
+    let mut batches = Vec::new();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut total = 0usize;
+    let mut rejected = 0usize;
+    let send = |item: StreamItem| {
+        sender
+            .send(item)
+            .map_err(|_| EtlError::sink("output sink stopped accepting events"))
+    };
+
+    let result = for_each_line(path, event_filter, |parsed| match parsed {
+        ParsedLine::Event(event) => {
+            batch.push(event);
+            total += 1;
+            if batch.len() >= batch_size {
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                batches.push(full);
+            }
+            Ok(())
+        }
+        ParsedLine::Rejected(line) => {
+            rejected += 1;
+            budget.record_error();
+            send(StreamItem::Rejected(line))?;
+            if budget.should_abort() {
+                return Err(budget.exceeded_error());
+            }
+            Ok(())
+        }
+    })
+    .and_then(|lines| {
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+        for batch in batches {
+            send(StreamItem::Events(batch))?;
+        }
+        send(StreamItem::Done(path.to_path_buf()))?;
+        Ok(lines)
+    });
+
+    match result {
+        Ok(lines) => {
+            budget.record_file(lines, rejected);
+            if !quiet_mode {
+                println!(" -> Success: {} events", total);
+            }
+        }
+        Err(e @ EtlError::ErrorBudgetExceeded { .. }) => {
+            eprintln!(" -> Aborted in file {:?}: {}", file_name, e);
+        }
+        Err(e) => {
+            eprintln!(" -> Error in file {:?}: {}", file_name, e);
+            budget.record_failed_file();
+        }
+    }
+}
+
+/// Takes the receiver by value so that it is dropped as soon as a sink fails,
+/// which unblocks the readers waiting on the channel.
+fn drain_stream(
+    receiver: mpsc::Receiver<StreamItem>,
+    output: &mut RunOutput,
+) -> Result<(), EtlError> {
+    for item in receiver {
+        match item {
+            StreamItem::Events(batch) => output.write_events(&batch)?,
+            StreamItem::Rejected(line) => output.write_rejected(&line)?,
+            StreamItem::Done(path) => {
+                output.file_done(&path);
+                output.save_progress()?;
+            }
+        }
+    }
     Ok(())
 }
 
 fn print_summary_normal_run(
-    total_files: usize,
-    total_lines: usize,
-    event_filter: &Option<String>,
-    output_file: &Option<String>,
+    summary: &RunSummary,
+    budget: &ErrorBudget,
+    config: &Config,
     elapsed: Instant,
-    quiet_mode: bool,
 ) {
-    if quiet_mode {
+    if config.quiet_mode {
         return;
     }
 
     println!("-------------------------------------------------");
     println!("Summary:");
-    println!("Total files: {}", total_files);
-    println!("Total events processed: {}", total_lines);
+    println!("Total files: {}", summary.files);
+    if summary.failed_files > 0 {
+        println!("Failed files: {}", summary.failed_files);
+    }
+    println!("Lines read: {}", summary.lines);
+    println!("Total events processed: {}", summary.events);
+    println!("Rejected lines: {}", summary.rejected);
 
-    if let Some(filter) = event_filter {
-        println!("Filter applied: {}", filter);
+    if summary.error_budget_exceeded {
+        println!(
+            "Error budget exceeded ({}){}",
+            budget.describe_limits(),
+            if summary.aborted { ", run aborted" } else { "" }
+        );
     }
 
-    if let Some(output) = output_file {
+    print_filters(config);
+
+    if let Some(output) = &config.output_file {
         println!("Output saved to: {}", output);
     }
 
+    if let Some(reject_file) = &config.reject_file {
+        println!("Rejected lines saved to: {}", reject_file);
+    }
+
     println!("Total time: {:.2?}", elapsed.elapsed());
 }
 
 fn print_summary_dry_run(
     total_files: usize,
     total_lines: usize,
-    event_filter: &Option<String>,
+    config: &Config,
     elapsed: Instant,
-    quiet_mode: bool,
 ) {
-    if quiet_mode {
+    if config.quiet_mode {
         return;
     }
     println!("-------------------------------------------------");
@@ -218,8 +860,43 @@ fn print_summary_dry_run(
     println!("Total files: {}", total_files);
     println!("Total lines/events: {}", total_lines);
 
-    if let Some(filter) = event_filter {
-        println!("Filter applied: {}", filter);
-    }
+    print_filters(config);
     println!("Total time: {:.2?}", elapsed);
 }
+
+fn print_filters(config: &Config) {
+    if !config.event_type_filter.is_empty() {
+        println!("Filter applied: {}", config.event_type_filter.join(", "));
+    }
+
+    if !config.exclude_event_type_filter.is_empty() {
+        println!(
+            "Excluded event types: {}",
+            config.exclude_event_type_filter.join(", ")
+        );
+    }
+
+    if !config.actor_filter.is_empty() {
+        println!("Actor filter: {}", config.actor_filter.join(", "));
+    }
+
+    if !config.repo_filter.is_empty() {
+        println!("Repository filter: {}", config.repo_filter.join(", "));
+    }
+
+    if !config.org_filter.is_empty() {
+        println!("Organization filter: {}", config.org_filter.join(", "));
+    }
+
+    if let Some(where_clause) = &config.where_clause {
+        println!("Where: {}", where_clause);
+    }
+
+    if config.since.is_some() || config.until.is_some() {
+        println!(
+            "Time window: {} .. {}",
+            config.since.as_deref().unwrap_or("*"),
+            config.until.as_deref().unwrap_or("*")
+        );
+    }
+}
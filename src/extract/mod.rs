@@ -1,3 +1,20 @@
 pub mod analysis;
+pub mod atomic;
+pub mod budget;
+pub mod checkpoint;
+pub mod compression;
+pub mod delimited;
+pub mod discovery;
+pub mod event_ref;
+pub mod expr;
 pub mod filters;
+pub mod incremental;
 pub mod json_lines;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod partition;
+pub mod sink;
+pub mod source;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod watch;
@@ -0,0 +1,299 @@
+use crate::EtlError;
+use crate::extract::atomic::AtomicFile;
+use crate::extract::sink::EventSink;
+use crate::model::github::GitHubEvent;
+use arrow_array::builder::{
+    BooleanBuilder, StringBuilder, TimestampMicrosecondBuilder, UInt32Builder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Column compression of a Parquet output: `none`, `snappy`, `gzip[:level]` or `zstd[:level]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParquetCompression {
+    None,
+    #[default]
+    Snappy,
+    Gzip(u32),
+    Zstd(i32),
+}
+
+impl FromStr for ParquetCompression {
+    type Err = EtlError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (codec, level) = match value.split_once(':') {
+            Some((codec, level)) => (codec, Some(level)),
+            None => (value, None),
+        };
+        let invalid = || {
+            EtlError::Config(format!(
+                "Invalid Parquet compression '{}': expected none, snappy, gzip[:0-9] or zstd[:1-22]",
+                value
+            ))
+        };
+        let level_or =
+            |default: i64| level.map_or(Ok(default), |level| level.parse().map_err(|_| invalid()));
+
+        let compression = match codec.to_ascii_lowercase().as_str() {
+            "none" | "uncompressed" if level.is_none() => ParquetCompression::None,
+            "snappy" if level.is_none() => ParquetCompression::Snappy,
+            "gzip" => ParquetCompression::Gzip(level_or(6)?.try_into().map_err(|_| invalid())?),
+            "zstd" => ParquetCompression::Zstd(level_or(3)?.try_into().map_err(|_| invalid())?),
+            _ => return Err(invalid()),
+        };
+        compression.to_parquet().map(|_| compression)
+    }
+}
+
+impl ParquetCompression {
+    fn to_parquet(self) -> Result<Compression, EtlError> {
+        let invalid_level = |e: parquet::errors::ParquetError| {
+            EtlError::Config(format!("Invalid Parquet compression level: {}", e))
+        };
+        Ok(match self {
+            ParquetCompression::None => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip(level) => {
+                Compression::GZIP(GzipLevel::try_new(level).map_err(invalid_level)?)
+            }
+            ParquetCompression::Zstd(level) => {
+                Compression::ZSTD(ZstdLevel::try_new(level).map_err(invalid_level)?)
+            }
+        })
+    }
+}
+
+/// Flattened Arrow schema of an event: one column per actor, repo and org member, one nullable
+/// column per scalar payload member, and the whole payload as JSON for the nested objects.
+pub fn event_schema() -> SchemaRef {
+    let text = |name: &str, nullable| Field::new(name, DataType::Utf8, nullable);
+    let id = |name: &str, nullable| Field::new(name, DataType::UInt64, nullable);
+
+    Arc::new(Schema::new(vec![
+        text("id", false),
+        text("type", false),
+        Field::new("public", DataType::Boolean, false),
+        Field::new(
+            "created_at",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        id("actor_id", false),
+        text("actor_login", false),
+        text("actor_display_login", true),
+        text("actor_gravatar_id", false),
+        text("actor_url", false),
+        text("actor_avatar_url", false),
+        id("repo_id", false),
+        text("repo_name", false),
+        text("repo_url", false),
+        id("org_id", true),
+        text("org_login", true),
+        text("org_gravatar_id", true),
+        text("org_url", true),
+        text("org_avatar_url", true),
+        text("payload_action", true),
+        text("payload_ref", true),
+        text("payload_ref_type", true),
+        text("payload_full_ref", true),
+        text("payload_pusher_type", true),
+        text("payload_master_branch", true),
+        text("payload_description", true),
+        text("payload_head", true),
+        text("payload_before", true),
+        id("payload_repository_id", true),
+        id("payload_push_id", true),
+        Field::new("payload_number", DataType::UInt32, true),
+        text("payload", false),
+    ]))
+}
+
+/// Converts events to one record batch following [`event_schema`].
+pub fn to_record_batch(
+    schema: &SchemaRef,
+    events: &[GitHubEvent],
+) -> Result<RecordBatch, EtlError> {
+    let rows = events.len();
+    let text = || StringBuilder::with_capacity(rows, rows * 16);
+    let (mut id, mut event_type, mut actor_login, mut actor_display_login) =
+        (text(), text(), text(), text());
+    let (mut actor_gravatar_id, mut actor_url, mut actor_avatar_url) = (text(), text(), text());
+    let (mut repo_name, mut repo_url) = (text(), text());
+    let (mut org_login, mut org_gravatar_id, mut org_url, mut org_avatar_url) =
+        (text(), text(), text(), text());
+    let (mut payload_action, mut payload_ref, mut payload_ref_type, mut payload) =
+        (text(), text(), text(), text());
+    let (mut payload_full_ref, mut payload_pusher_type, mut payload_master_branch) =
+        (text(), text(), text());
+    let (mut payload_description, mut payload_head, mut payload_before) = (text(), text(), text());
+    let mut public = BooleanBuilder::with_capacity(rows);
+    let mut created_at = TimestampMicrosecondBuilder::with_capacity(rows).with_timezone("UTC");
+    let number = || UInt64Builder::with_capacity(rows);
+    let (mut actor_id, mut repo_id, mut org_id) = (number(), number(), number());
+    let (mut payload_repository_id, mut payload_push_id) = (number(), number());
+    let mut payload_number = UInt32Builder::with_capacity(rows);
+
+    for event in events {
+        id.append_value(&event.id);
+        event_type.append_value(event.event_type.as_str());
+        public.append_value(event.public);
+        created_at.append_value(event.created_at.timestamp_micros());
+
+        actor_id.append_value(event.actor.id);
+        actor_login.append_value(&event.actor.login);
+        actor_display_login.append_option(event.actor.display_login.as_deref());
+        actor_gravatar_id.append_value(&event.actor.gravatar_id);
+        actor_url.append_value(&event.actor.url);
+        actor_avatar_url.append_value(&event.actor.avatar_url);
+
+        repo_id.append_value(event.repo.id);
+        repo_name.append_value(&event.repo.name);
+        repo_url.append_value(&event.repo.url);
+
+        let org = event.org.as_ref();
+        org_id.append_option(org.map(|org| org.id));
+        org_login.append_option(org.map(|org| &org.login));
+        org_gravatar_id.append_option(org.map(|org| &org.gravatar_id));
+        org_url.append_option(org.map(|org| &org.url));
+        org_avatar_url.append_option(org.map(|org| &org.avatar_url));
+
+        payload_action.append_option(event.payload.action.as_deref());
+        payload_ref.append_option(event.payload.r#ref.as_deref());
+        payload_ref_type.append_option(event.payload.ref_type.as_deref());
+        payload_full_ref.append_option(event.payload.full_ref.as_deref());
+        payload_pusher_type.append_option(event.payload.pusher_type.as_deref());
+        payload_master_branch.append_option(event.payload.master_branch.as_deref());
+        payload_description.append_option(event.payload.description.as_deref());
+        payload_head.append_option(event.payload.head.as_deref());
+        payload_before.append_option(event.payload.before.as_deref());
+        payload_repository_id.append_option(event.payload.repository_id);
+        payload_push_id.append_option(event.payload.push_id);
+        payload_number.append_option(event.payload.number);
+        let payload_json = serde_json::to_string(&event.payload).map_err(|e| {
+            EtlError::sink_caused_by(format!("Failed to serialize event: {}", e), e)
+        })?;
+        payload.append_value(payload_json);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(id.finish()),
+        Arc::new(event_type.finish()),
+        Arc::new(public.finish()),
+        Arc::new(created_at.finish()),
+        Arc::new(actor_id.finish()),
+        Arc::new(actor_login.finish()),
+        Arc::new(actor_display_login.finish()),
+        Arc::new(actor_gravatar_id.finish()),
+        Arc::new(actor_url.finish()),
+        Arc::new(actor_avatar_url.finish()),
+        Arc::new(repo_id.finish()),
+        Arc::new(repo_name.finish()),
+        Arc::new(repo_url.finish()),
+        Arc::new(org_id.finish()),
+        Arc::new(org_login.finish()),
+        Arc::new(org_gravatar_id.finish()),
+        Arc::new(org_url.finish()),
+        Arc::new(org_avatar_url.finish()),
+        Arc::new(payload_action.finish()),
+        Arc::new(payload_ref.finish()),
+        Arc::new(payload_ref_type.finish()),
+        Arc::new(payload_full_ref.finish()),
+        Arc::new(payload_pusher_type.finish()),
+        Arc::new(payload_master_branch.finish()),
+        Arc::new(payload_description.finish()),
+        Arc::new(payload_head.finish()),
+        Arc::new(payload_before.finish()),
+        Arc::new(payload_repository_id.finish()),
+        Arc::new(payload_push_id.finish()),
+        Arc::new(payload_number.finish()),
+        Arc::new(payload.finish()),
+    ];
+
+    RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|e| EtlError::sink_caused_by(format!("Failed to build record batch: {}", e), e))
+}
+
+/// Parquet file sink. Batches are buffered by the Arrow writer and flushed as row groups
+/// of `row_group_size` rows; the footer is written by [`EventSink::finish`].
+pub struct ParquetSink {
+    path: PathBuf,
+    schema: SchemaRef,
+    writer: Option<ArrowWriter<File>>,
+    pending: Option<AtomicFile>,
+}
+
+impl ParquetSink {
+    pub fn create(
+        output_path: &str,
+        row_group_size: usize,
+        compression: ParquetCompression,
+    ) -> Result<Self, EtlError> {
+        if row_group_size == 0 {
+            return Err(EtlError::Config(
+                "row_group_size must be greater than zero".to_string(),
+            ));
+        }
+
+        let pending = AtomicFile::create(output_path)
+            .map_err(|e| EtlError::io("Failed to create output file", output_path, e))?;
+        let file = pending
+            .file()
+            .map_err(|e| EtlError::io("Failed to create output file", output_path, e))?;
+        let properties = WriterProperties::builder()
+            .set_max_row_group_row_count(Some(row_group_size))
+            .set_compression(compression.to_parquet()?)
+            .build();
+        let schema = event_schema();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))
+            .map_err(|e| parquet_error(output_path, e))?;
+
+        Ok(ParquetSink {
+            path: PathBuf::from(output_path),
+            schema,
+            writer: Some(writer),
+            pending: Some(pending),
+        })
+    }
+}
+
+impl EventSink for ParquetSink {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Err(EtlError::sink("Parquet output is already closed"));
+        };
+        let batch = to_record_batch(&self.schema, events)?;
+        writer
+            .write(&batch)
+            .map_err(|e| parquet_error(&self.path.display().to_string(), e))
+    }
+
+    fn finish(&mut self) -> Result<(), EtlError> {
+        let (Some(writer), Some(pending)) = (self.writer.take(), self.pending.take()) else {
+            return Ok(());
+        };
+        writer
+            .close()
+            .map_err(|e| parquet_error(&self.path.display().to_string(), e))?;
+        pending
+            .commit()
+            .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))
+    }
+}
+
+fn parquet_error(path: &str, error: parquet::errors::ParquetError) -> EtlError {
+    EtlError::sink_caused_by(
+        format!("Failed to write Parquet file {}: {}", path, error),
+        error,
+    )
+}
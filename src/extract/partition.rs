@@ -0,0 +1,405 @@
+use crate::EtlError;
+use crate::extract::atomic::AtomicFile;
+use crate::extract::compression::{CompressedWriter, OutputCompression};
+use crate::extract::sink::EventSink;
+use crate::model::github::GitHubEvent;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Value Hive-style engines use for a missing partition value.
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Most parts a [`PartitionedSink`] keeps open at once. Past it, the part written to least
+/// recently is closed, and its partition gets a new part if it is written to again.
+pub const MAX_OPEN_PARTS: usize = 256;
+
+/// A directory level of a partitioned output, written as `<name>=<value>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionKey {
+    Type,
+    Date,
+    Hour,
+    Org,
+    RepoOwner,
+}
+
+impl PartitionKey {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PartitionKey::Type => "type",
+            PartitionKey::Date => "date",
+            PartitionKey::Hour => "hour",
+            PartitionKey::Org => "org",
+            PartitionKey::RepoOwner => "repo_owner",
+        }
+    }
+
+    fn value(&self, event: &GitHubEvent) -> String {
+        match self {
+            PartitionKey::Type => escape(event.event_type.as_str()),
+            PartitionKey::Date => event.created_at.format("%Y-%m-%d").to_string(),
+            PartitionKey::Hour => event.created_at.format("%H").to_string(),
+            PartitionKey::Org => event
+                .org
+                .as_ref()
+                .map_or(DEFAULT_PARTITION.to_string(), |org| escape(&org.login)),
+            PartitionKey::RepoOwner => match event.repo.name.split_once('/') {
+                Some((owner, _)) if !owner.is_empty() => escape(owner),
+                _ => DEFAULT_PARTITION.to_string(),
+            },
+        }
+    }
+}
+
+impl FromStr for PartitionKey {
+    type Err = EtlError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "type" => Ok(PartitionKey::Type),
+            "date" => Ok(PartitionKey::Date),
+            "hour" => Ok(PartitionKey::Hour),
+            "org" => Ok(PartitionKey::Org),
+            "repo_owner" | "repo-owner" | "owner" => Ok(PartitionKey::RepoOwner),
+            _ => Err(EtlError::Config(format!(
+                "Invalid partition key '{}'. Valid keys are: type, date, hour, org, repo_owner",
+                value
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for PartitionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Percent-encodes everything but `[A-Za-z0-9._-]` so a value is always one path segment.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'_' | b'-' => {
+                escaped.push(byte as char)
+            }
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    if escaped.is_empty() || escaped == "." || escaped == ".." {
+        DEFAULT_PARTITION.to_string()
+    } else {
+        escaped
+    }
+}
+
+/// When to start a new part file within a partition. `None` means no limit.
+/// Sizes are counted before compression.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rotation {
+    pub max_events: Option<usize>,
+    pub max_bytes: Option<u64>,
+}
+
+impl Rotation {
+    fn is_full(&self, events: usize, bytes: u64) -> bool {
+        self.max_events.is_some_and(|max| events >= max)
+            || self.max_bytes.is_some_and(|max| bytes >= max)
+    }
+}
+
+/// The part currently written in one partition directory, under a temporary name until
+/// the sink is finished.
+struct Part {
+    path: PathBuf,
+    pending: AtomicFile,
+    writer: CompressedWriter,
+    events: usize,
+    bytes: u64,
+    /// When the part was last written to, to close the least recently used one first.
+    last_used: u64,
+}
+
+impl Part {
+    fn create(dir: &Path, index: usize, compression: OutputCompression) -> Result<Self, EtlError> {
+        let mut name = format!("part-{:04}.jsonl", index);
+        if let Some(extension) = compression.codec.extension() {
+            name = format!("{}.{}", name, extension);
+        }
+        let path = dir.join(name);
+        let pending = AtomicFile::create(&path)
+            .map_err(|e| EtlError::io("Failed to create output file", &path, e))?;
+        let writer = pending
+            .file()
+            .and_then(|file| compression.writer(file))
+            .map_err(|e| EtlError::io("Failed to create output file", &path, e))?;
+        Ok(Part {
+            path,
+            pending,
+            writer,
+            events: 0,
+            bytes: 0,
+            last_used: 0,
+        })
+    }
+
+    /// Flushes and closes the part; it replaces its target once the whole sink is finished.
+    fn finish(self) -> Result<AtomicFile, EtlError> {
+        let mut pending = self.pending;
+        self.writer
+            .finish()
+            .and_then(|_| pending.close())
+            .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))?;
+        Ok(pending)
+    }
+}
+
+/// JSON Lines output split into Hive-style directories, e.g.
+/// `out/type=PushEvent/date=2024-01-01/hour=15/part-0000.jsonl`, with a `.gz`, `.zst`
+/// or `.bz2` suffix when compressed.
+///
+/// Parts become visible when the sink is finished. Part files an earlier run left in the
+/// partitions written to are then removed, so rerunning replaces a partition instead of
+/// adding duplicates, and a failed run leaves the previous partitions as they were.
+/// An appending sink keeps the existing parts and numbers its own after them.
+pub struct PartitionedSink {
+    root: PathBuf,
+    keys: Vec<PartitionKey>,
+    rotation: Rotation,
+    compression: OutputCompression,
+    append: bool,
+    /// At most [`MAX_OPEN_PARTS`] open parts, by partition directory.
+    parts: HashMap<PathBuf, Part>,
+    /// Counts the events written, to order the open parts by last use.
+    clock: u64,
+    /// Index of the next part file of each partition directory written to.
+    next_index: HashMap<PathBuf, usize>,
+    finished: Vec<AtomicFile>,
+    written: HashSet<PathBuf>,
+}
+
+impl PartitionedSink {
+    /// Replaces the partitions the run writes to.
+    pub fn create(
+        root: &str,
+        keys: &[PartitionKey],
+        rotation: Rotation,
+        compression: OutputCompression,
+    ) -> Result<Self, EtlError> {
+        Self::open(root, keys, rotation, compression, false)
+    }
+
+    /// Adds new part files next to the existing ones instead of replacing them.
+    pub fn append(
+        root: &str,
+        keys: &[PartitionKey],
+        rotation: Rotation,
+        compression: OutputCompression,
+    ) -> Result<Self, EtlError> {
+        Self::open(root, keys, rotation, compression, true)
+    }
+
+    fn open(
+        root: &str,
+        keys: &[PartitionKey],
+        rotation: Rotation,
+        compression: OutputCompression,
+        append: bool,
+    ) -> Result<Self, EtlError> {
+        if rotation.max_events == Some(0) || rotation.max_bytes == Some(0) {
+            return Err(EtlError::Config(
+                "Part size limits must be greater than zero".to_string(),
+            ));
+        }
+        fs::create_dir_all(root)
+            .map_err(|e| EtlError::io("Failed to create output directory", root, e))?;
+
+        Ok(PartitionedSink {
+            root: PathBuf::from(root),
+            keys: keys.to_vec(),
+            rotation,
+            compression,
+            append,
+            parts: HashMap::new(),
+            clock: 0,
+            next_index: HashMap::new(),
+            finished: Vec::new(),
+            written: HashSet::new(),
+        })
+    }
+
+    pub fn partition_dir(&self, event: &GitHubEvent) -> PathBuf {
+        let mut dir = self.root.clone();
+        for key in &self.keys {
+            dir.push(format!("{}={}", key.name(), key.value(event)));
+        }
+        dir
+    }
+
+    fn part_for(&mut self, dir: PathBuf) -> Result<&mut Part, EtlError> {
+        if !self.parts.contains_key(&dir) {
+            if self.parts.len() >= MAX_OPEN_PARTS {
+                self.close_least_recently_used()?;
+            }
+            let part = self.create_part(&dir)?;
+            self.parts.insert(dir.clone(), part);
+        }
+
+        self.clock += 1;
+        let part = self.parts.get_mut(&dir).expect("part was just inserted");
+        part.last_used = self.clock;
+        if self.rotation.is_full(part.events, part.bytes) {
+            let next_index = self.next_index.get_mut(&dir).expect("partition was opened");
+            let next = Part::create(&dir, *next_index, self.compression)?;
+            *next_index += 1;
+            let full = std::mem::replace(part, next).finish()?;
+            self.finished.push(full);
+        }
+        Ok(part)
+    }
+
+    fn close_least_recently_used(&mut self) -> Result<(), EtlError> {
+        let oldest = self
+            .parts
+            .iter()
+            .min_by_key(|(_, part)| part.last_used)
+            .map(|(dir, _)| dir.clone());
+        if let Some(part) = oldest.and_then(|dir| self.parts.remove(&dir)) {
+            self.finished.push(part.finish()?);
+        }
+        Ok(())
+    }
+
+    fn create_part(&mut self, dir: &Path) -> Result<Part, EtlError> {
+        let index = match self.next_index.get(dir) {
+            Some(index) => *index,
+            None => {
+                fs::create_dir_all(dir)
+                    .map_err(|e| EtlError::io("Failed to create output directory", dir, e))?;
+                if self.append {
+                    next_part_index(dir)?
+                } else {
+                    0
+                }
+            }
+        };
+        let part = Part::create(dir, index, self.compression)?;
+        self.next_index.insert(dir.to_path_buf(), index + 1);
+        Ok(part)
+    }
+
+    /// Moves every finished or open part into place.
+    fn commit_parts(&mut self) -> Result<(), EtlError> {
+        for (_, part) in self.parts.drain() {
+            self.finished.push(part.finish()?);
+        }
+        for pending in self.finished.drain(..) {
+            let path = pending.target().to_path_buf();
+            pending
+                .commit()
+                .map_err(|e| EtlError::io("Failed to write to file", &path, e))?;
+            self.written.insert(path);
+        }
+        Ok(())
+    }
+}
+
+impl EventSink for PartitionedSink {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        for event in events {
+            let mut line = serde_json::to_string(event).map_err(|e| {
+                EtlError::sink_caused_by(format!("Failed to serialize event: {}", e), e)
+            })?;
+            line.push('\n');
+
+            let dir = self.partition_dir(event);
+            let part = self.part_for(dir)?;
+            part.writer
+                .write_all(line.as_bytes())
+                .map_err(|e| EtlError::io("Failed to write to file", &part.path, e))?;
+            part.events += 1;
+            part.bytes += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Commits the parts written so far; later events go to new parts.
+    fn checkpoint(&mut self) -> Result<(), EtlError> {
+        self.commit_parts()
+    }
+
+    fn finish(&mut self) -> Result<(), EtlError> {
+        self.commit_parts()?;
+        if self.append {
+            return Ok(());
+        }
+        let dirs: HashSet<&Path> = self
+            .written
+            .iter()
+            .filter_map(|path| path.parent())
+            .collect();
+        for dir in dirs {
+            remove_stale_parts(dir, &self.written)?;
+        }
+        Ok(())
+    }
+}
+
+/// Index following the highest part file already in `dir`.
+fn next_part_index(dir: &Path) -> Result<usize, EtlError> {
+    let entries = fs::read_dir(dir).map_err(|e| EtlError::io("Unable to read folder", dir, e))?;
+    let mut next = 0;
+    for entry in entries {
+        let entry = entry.map_err(|e| EtlError::io("Unable to read folder", dir, e))?;
+        let index = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix("part-"))
+            .and_then(|rest| rest.split('.').next())
+            .and_then(|digits| digits.parse::<usize>().ok());
+        if let Some(index) = index {
+            next = next.max(index + 1);
+        }
+    }
+    Ok(next)
+}
+
+/// Removes every part file below `root`, leaving the directories in place.
+pub fn remove_all_parts(root: &Path) -> Result<(), EtlError> {
+    if !root.is_dir() {
+        return Ok(());
+    }
+    remove_stale_parts(root, &HashSet::new())?;
+    let entries = fs::read_dir(root).map_err(|e| EtlError::io("Unable to read folder", root, e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| EtlError::io("Unable to read folder", root, e))?;
+        let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+        if is_dir {
+            remove_all_parts(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the part files of `dir` that this run did not write.
+fn remove_stale_parts(dir: &Path, written: &HashSet<PathBuf>) -> Result<(), EtlError> {
+    let entries = fs::read_dir(dir).map_err(|e| EtlError::io("Unable to read folder", dir, e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| EtlError::io("Unable to read folder", dir, e))?
+            .path();
+        let is_part = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("part-") && name.contains(".jsonl"));
+        if is_part && path.is_file() && !written.contains(&path) {
+            fs::remove_file(&path)
+                .map_err(|e| EtlError::io("Failed to remove stale part", &path, e))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,376 @@
+use crate::extract::atomic::AtomicFile;
+use crate::extract::compression::{CompressedWriter, Compression, OutputCompression};
+use crate::extract::delimited::DelimitedSink;
+use crate::extract::partition::{PartitionedSink, Rotation};
+use crate::model::github::GitHubEvent;
+use crate::{Config, EtlError};
+use serde::Serialize;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Destination for processed events. Batches arrive in the order they should be written.
+///
+/// File sinks write to a temporary file and only replace their output in [`EventSink::finish`],
+/// so a run that fails or is dropped before finishing leaves the previous output untouched.
+pub trait EventSink {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError>;
+
+    /// Makes everything written so far durable, so that a later run can append after it.
+    fn checkpoint(&mut self) -> Result<(), EtlError> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), EtlError> {
+        Ok(())
+    }
+}
+
+/// File format of the `--output` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    JsonLines,
+    Parquet,
+    Csv,
+    Tsv,
+    Sqlite,
+}
+
+impl FromStr for OutputFormat {
+    type Err = EtlError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "jsonl" | "json" | "ndjson" => Ok(OutputFormat::JsonLines),
+            "parquet" => Ok(OutputFormat::Parquet),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "sqlite" => Ok(OutputFormat::Sqlite),
+            _ => Err(EtlError::Config(format!(
+                "Invalid output format '{}'. Valid formats are: jsonl, parquet, csv, tsv, sqlite",
+                value
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputFormat::JsonLines => "jsonl",
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Sqlite => "sqlite",
+        })
+    }
+}
+
+/// Opens the sink writing `output_path` in the format chosen by `config`.
+/// With partition keys, `output_path` is the root directory of the partitions.
+pub fn open_output(output_path: &str, config: &Config) -> Result<Box<dyn EventSink>, EtlError> {
+    let compression = OutputCompression::resolve(
+        Path::new(output_path),
+        config.compress,
+        config.compress_level,
+    )?;
+    if compression.codec != Compression::None
+        && matches!(
+            config.output_format,
+            OutputFormat::Parquet | OutputFormat::Sqlite
+        )
+    {
+        return Err(EtlError::Config(format!(
+            "{} output cannot be compressed as a whole{}",
+            config.output_format,
+            if config.output_format == OutputFormat::Parquet {
+                ", use --parquet-compression instead"
+            } else {
+                ""
+            }
+        )));
+    }
+
+    if !config.partition_by.is_empty() {
+        if config.output_format != OutputFormat::JsonLines {
+            return Err(EtlError::Config(
+                "Partitioned output is only available in the jsonl format".to_string(),
+            ));
+        }
+        let rotation = Rotation {
+            max_events: config.max_part_events,
+            max_bytes: config.max_part_bytes,
+        };
+        let open = if appends(config) {
+            PartitionedSink::append
+        } else {
+            PartitionedSink::create
+        };
+        return Ok(Box::new(open(
+            output_path,
+            &config.partition_by,
+            rotation,
+            compression,
+        )?));
+    }
+
+    let is_delimited = matches!(config.output_format, OutputFormat::Csv | OutputFormat::Tsv);
+    if !config.columns.is_empty() && !is_delimited {
+        return Err(EtlError::Config(
+            "--columns only applies to csv and tsv output".to_string(),
+        ));
+    }
+
+    let append = appends(config);
+    match config.output_format {
+        OutputFormat::JsonLines if append => Ok(Box::new(JsonLinesSink::append_compressed(
+            output_path,
+            compression,
+        )?)),
+        OutputFormat::JsonLines => Ok(Box::new(JsonLinesSink::create_compressed(
+            output_path,
+            compression,
+        )?)),
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let delimiter = if config.output_format == OutputFormat::Csv {
+                b','
+            } else {
+                b'\t'
+            };
+            let open = if append {
+                DelimitedSink::append
+            } else {
+                DelimitedSink::create
+            };
+            Ok(Box::new(open(
+                output_path,
+                &config.columns,
+                delimiter,
+                compression,
+            )?))
+        }
+        #[cfg(feature = "parquet")]
+        OutputFormat::Parquet => {
+            use crate::extract::parquet::{ParquetCompression, ParquetSink};
+            let compression: ParquetCompression = config.parquet_compression.parse()?;
+            Ok(Box::new(ParquetSink::create(
+                output_path,
+                config.row_group_size,
+                compression,
+            )?))
+        }
+        #[cfg(not(feature = "parquet"))]
+        OutputFormat::Parquet => Err(EtlError::Config(
+            "Parquet output requires building with the `parquet` feature".to_string(),
+        )),
+        #[cfg(feature = "sqlite")]
+        OutputFormat::Sqlite => Ok(Box::new(crate::extract::sqlite::SqliteSink::open(
+            output_path,
+        )?)),
+        #[cfg(not(feature = "sqlite"))]
+        OutputFormat::Sqlite => Err(EtlError::Config(
+            "SQLite output requires building with the `sqlite` feature".to_string(),
+        )),
+    }
+}
+
+/// Whether the run appends to its outputs in place instead of replacing them when it ends,
+/// as checkpointed, incremental and watching runs do.
+pub fn appends(config: &Config) -> bool {
+    config.checkpoint_file.is_some() || config.incremental_state.is_some() || config.watch
+}
+
+pub struct JsonLinesSink {
+    path: PathBuf,
+    compression: OutputCompression,
+    writer: Option<CompressedWriter>,
+    /// Temporary file replacing `path` on finish; `None` when appending in place.
+    pending: Option<AtomicFile>,
+}
+
+impl JsonLinesSink {
+    /// Replaces `output_path` once the sink is finished.
+    pub fn create_compressed(
+        output_path: &str,
+        compression: OutputCompression,
+    ) -> Result<Self, EtlError> {
+        let (pending, writer) = create_atomic(output_path, compression)
+            .map_err(|e| EtlError::io("Failed to create output file", output_path, e))?;
+
+        Ok(JsonLinesSink {
+            path: PathBuf::from(output_path),
+            compression,
+            writer: Some(writer),
+            pending: Some(pending),
+        })
+    }
+
+    /// Appends to `output_path`, compressed according to its extension.
+    pub fn append(output_path: &str) -> Result<Self, EtlError> {
+        Self::append_compressed(
+            output_path,
+            OutputCompression::from_path(Path::new(output_path)),
+        )
+    }
+
+    /// Appends to `output_path`. A compressed file gets a new gzip member or zstd frame,
+    /// which readers of the format see as one continuous stream.
+    pub fn append_compressed(
+        output_path: &str,
+        compression: OutputCompression,
+    ) -> Result<Self, EtlError> {
+        let writer = open_append(output_path, compression)
+            .map_err(|e| EtlError::io("Failed to open output file", output_path, e))?;
+
+        Ok(JsonLinesSink {
+            path: PathBuf::from(output_path),
+            compression,
+            writer: Some(writer),
+            pending: None,
+        })
+    }
+}
+
+impl EventSink for JsonLinesSink {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        let writer = open_writer(&mut self.writer, &self.path)?;
+        for event in events {
+            let json_line = serde_json::to_string(event).map_err(|e| {
+                EtlError::sink_caused_by(format!("Failed to serialize event: {}", e), e)
+            })?;
+
+            writeln!(writer, "{}", json_line)
+                .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))?;
+        }
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) -> Result<(), EtlError> {
+        sync_writer(&mut self.writer, self.compression)
+            .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))
+    }
+
+    fn finish(&mut self) -> Result<(), EtlError> {
+        finish_writer(&mut self.writer, &mut self.pending)
+            .map_err(|e| EtlError::io("Failed to write to file", &self.path, e))
+    }
+}
+
+fn create_atomic(
+    path: &str,
+    compression: OutputCompression,
+) -> io::Result<(AtomicFile, CompressedWriter)> {
+    let pending = AtomicFile::create(path)?;
+    let writer = compression.writer(pending.file()?)?;
+    Ok((pending, writer))
+}
+
+fn open_append(path: &str, compression: OutputCompression) -> io::Result<CompressedWriter> {
+    let file = OpenOptions::new().append(true).create(true).open(path)?;
+    compression.writer(file)
+}
+
+fn open_writer<'a>(
+    writer: &'a mut Option<CompressedWriter>,
+    path: &Path,
+) -> Result<&'a mut CompressedWriter, EtlError> {
+    writer
+        .as_mut()
+        .ok_or_else(|| EtlError::sink(format!("Output {} was already finished", path.display())))
+}
+
+/// Ends the compressed stream and syncs the file, then continues with a new stream
+/// that readers of the format see as the continuation of the previous one.
+fn sync_writer(
+    writer: &mut Option<CompressedWriter>,
+    compression: OutputCompression,
+) -> io::Result<()> {
+    if let Some(current) = writer.take() {
+        let file = current.finish()?;
+        file.sync_all()?;
+        *writer = Some(compression.writer(file)?);
+    }
+    Ok(())
+}
+
+/// Flushes the writer and syncs the file, then moves a pending temporary file into place.
+fn finish_writer(
+    writer: &mut Option<CompressedWriter>,
+    pending: &mut Option<AtomicFile>,
+) -> io::Result<()> {
+    if let Some(writer) = writer.take() {
+        writer.finish()?.sync_all()?;
+    }
+    match pending.take() {
+        Some(pending) => pending.commit(),
+        None => Ok(()),
+    }
+}
+
+/// An input line that could not be parsed, kept for auditing.
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedLine {
+    pub source: String,
+    pub line: usize,
+    pub error: String,
+    pub raw: String,
+}
+
+/// Dead-letter file: one JSON object per rejected line, compressed according to its extension.
+/// Like the event outputs, it replaces the previous file only when finished, unless appending.
+pub struct RejectSink {
+    path: PathBuf,
+    compression: OutputCompression,
+    writer: Option<CompressedWriter>,
+    pending: Option<AtomicFile>,
+}
+
+impl RejectSink {
+    pub fn create(reject_path: &str) -> Result<Self, EtlError> {
+        let compression = OutputCompression::from_path(Path::new(reject_path));
+        let (pending, writer) = create_atomic(reject_path, compression)
+            .map_err(|e| EtlError::io("Failed to create reject file", reject_path, e))?;
+
+        Ok(RejectSink {
+            path: PathBuf::from(reject_path),
+            compression,
+            writer: Some(writer),
+            pending: Some(pending),
+        })
+    }
+
+    pub fn append(reject_path: &str) -> Result<Self, EtlError> {
+        let compression = OutputCompression::from_path(Path::new(reject_path));
+        let writer = open_append(reject_path, compression)
+            .map_err(|e| EtlError::io("Failed to open reject file", reject_path, e))?;
+
+        Ok(RejectSink {
+            path: PathBuf::from(reject_path),
+            compression,
+            writer: Some(writer),
+            pending: None,
+        })
+    }
+
+    pub fn write(&mut self, rejected: &RejectedLine) -> Result<(), EtlError> {
+        let json_line = serde_json::to_string(rejected).map_err(|e| {
+            EtlError::sink_caused_by(format!("Failed to serialize rejected line: {}", e), e)
+        })?;
+
+        writeln!(open_writer(&mut self.writer, &self.path)?, "{}", json_line)
+            .map_err(|e| EtlError::io("Failed to write to reject file", &self.path, e))
+    }
+
+    pub fn checkpoint(&mut self) -> Result<(), EtlError> {
+        sync_writer(&mut self.writer, self.compression)
+            .map_err(|e| EtlError::io("Failed to write to reject file", &self.path, e))
+    }
+
+    pub fn finish(&mut self) -> Result<(), EtlError> {
+        finish_writer(&mut self.writer, &mut self.pending)
+            .map_err(|e| EtlError::io("Failed to write to reject file", &self.path, e))
+    }
+}
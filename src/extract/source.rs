@@ -0,0 +1,76 @@
+use crate::extract::compression::{Compression, decompress, open_reader};
+use crate::extract::discovery::InputDiscovery;
+use crate::{Config, EtlError};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Input name standing for the standard input.
+pub const STDIN: &str = "-";
+
+/// Where a run gets its inputs from.
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    /// Folders and files, named directly or listed in a manifest.
+    Paths(InputDiscovery),
+    /// Lines piped into the process, read as the single input `-`.
+    Stdin,
+}
+
+impl InputSource {
+    pub fn from_config(
+        config: &Config,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Self, EtlError> {
+        let mut discovery = InputDiscovery::from_config(config, since, until)?;
+        if let Some(manifest) = &config.manifest {
+            discovery.roots.extend(read_manifest(Path::new(manifest))?);
+        }
+        if discovery.roots.iter().any(|root| root == Path::new(STDIN)) {
+            if discovery.roots.len() > 1 {
+                return Err(EtlError::Config(
+                    "The standard input cannot be read together with other inputs".to_string(),
+                ));
+            }
+            return Ok(InputSource::Stdin);
+        }
+        if discovery.roots.is_empty() {
+            return Err(EtlError::Config("No input given".to_string()));
+        }
+        Ok(InputSource::Paths(discovery))
+    }
+
+    pub fn list(&self) -> Result<Vec<PathBuf>, EtlError> {
+        match self {
+            InputSource::Paths(discovery) => discovery.list(),
+            InputSource::Stdin => Ok(vec![PathBuf::from(STDIN)]),
+        }
+    }
+}
+
+/// Reads the inputs listed in a manifest, one folder or file per line. Blank lines and
+/// lines starting with `#` are skipped, relative paths are relative to the manifest.
+pub fn read_manifest(path: &Path) -> Result<Vec<PathBuf>, EtlError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| EtlError::io("Failed to read manifest file", path, e))?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect())
+}
+
+/// Opens an input for line reading. Files are decompressed based on their extension, the
+/// standard input based on its first bytes.
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead + Send>> {
+    if path != Path::new(STDIN) {
+        return open_reader(path);
+    }
+    let mut reader = BufReader::new(io::stdin());
+    let codec = Compression::from_magic(reader.fill_buf()?);
+    decompress(reader, codec)
+}
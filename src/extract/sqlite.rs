@@ -0,0 +1,178 @@
+use crate::EtlError;
+use crate::extract::sink::EventSink;
+use crate::model::github::GitHubEvent;
+use rusqlite::{Connection, Transaction, params};
+use std::path::{Path, PathBuf};
+
+/// Largest number of events written in one transaction.
+const TRANSACTION_SIZE: usize = 10_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS actors (
+    id INTEGER PRIMARY KEY,
+    login TEXT NOT NULL,
+    display_login TEXT,
+    gravatar_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    avatar_url TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS repos (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS orgs (
+    id INTEGER PRIMARY KEY,
+    login TEXT NOT NULL,
+    gravatar_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    avatar_url TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL,
+    actor_id INTEGER NOT NULL REFERENCES actors(id),
+    repo_id INTEGER NOT NULL REFERENCES repos(id),
+    org_id INTEGER REFERENCES orgs(id),
+    public INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    payload TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_type ON events(type);
+CREATE INDEX IF NOT EXISTS events_created_at ON events(created_at);
+CREATE INDEX IF NOT EXISTS events_repo_id ON events(repo_id);
+";
+
+const UPSERT_ACTOR: &str = "
+INSERT INTO actors (id, login, display_login, gravatar_id, url, avatar_url)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+ON CONFLICT(id) DO UPDATE SET
+    login = excluded.login,
+    display_login = excluded.display_login,
+    gravatar_id = excluded.gravatar_id,
+    url = excluded.url,
+    avatar_url = excluded.avatar_url";
+
+const UPSERT_REPO: &str = "
+INSERT INTO repos (id, name, url)
+VALUES (?1, ?2, ?3)
+ON CONFLICT(id) DO UPDATE SET name = excluded.name, url = excluded.url";
+
+const UPSERT_ORG: &str = "
+INSERT INTO orgs (id, login, gravatar_id, url, avatar_url)
+VALUES (?1, ?2, ?3, ?4, ?5)
+ON CONFLICT(id) DO UPDATE SET
+    login = excluded.login,
+    gravatar_id = excluded.gravatar_id,
+    url = excluded.url,
+    avatar_url = excluded.avatar_url";
+
+const UPSERT_EVENT: &str = "
+INSERT INTO events (id, type, actor_id, repo_id, org_id, public, created_at, payload)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+ON CONFLICT(id) DO UPDATE SET
+    type = excluded.type,
+    actor_id = excluded.actor_id,
+    repo_id = excluded.repo_id,
+    org_id = excluded.org_id,
+    public = excluded.public,
+    created_at = excluded.created_at,
+    payload = excluded.payload";
+
+/// SQLite database with normalized `events`, `actors`, `repos` and `orgs` tables.
+/// Every row is upserted on its id, so loading the same input twice leaves the database unchanged.
+/// An existing database is kept and added to.
+pub struct SqliteSink {
+    path: PathBuf,
+    connection: Connection,
+}
+
+impl SqliteSink {
+    pub fn open(database_path: &str) -> Result<Self, EtlError> {
+        let path = PathBuf::from(database_path);
+        let connection = Connection::open(&path).map_err(|e| sqlite_error(&path, e))?;
+        connection
+            .execute_batch(
+                "PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA foreign_keys = ON;",
+            )
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .map_err(|e| sqlite_error(&path, e))?;
+
+        Ok(SqliteSink { path, connection })
+    }
+}
+
+impl EventSink for SqliteSink {
+    fn write_batch(&mut self, events: &[GitHubEvent]) -> Result<(), EtlError> {
+        for chunk in events.chunks(TRANSACTION_SIZE) {
+            let transaction = self
+                .connection
+                .transaction()
+                .map_err(|e| sqlite_error(&self.path, e))?;
+            upsert_events(&transaction, chunk)
+                .and_then(|_| transaction.commit())
+                .map_err(|e| sqlite_error(&self.path, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn upsert_events(transaction: &Transaction<'_>, events: &[GitHubEvent]) -> rusqlite::Result<()> {
+    let mut upsert_actor = transaction.prepare_cached(UPSERT_ACTOR)?;
+    let mut upsert_repo = transaction.prepare_cached(UPSERT_REPO)?;
+    let mut upsert_org = transaction.prepare_cached(UPSERT_ORG)?;
+    let mut upsert_event = transaction.prepare_cached(UPSERT_EVENT)?;
+
+    for event in events {
+        let actor = &event.actor;
+        upsert_actor.execute(params![
+            actor.id as i64,
+            actor.login,
+            actor.display_login,
+            actor.gravatar_id,
+            actor.url,
+            actor.avatar_url
+        ])?;
+        upsert_repo.execute(params![
+            event.repo.id as i64,
+            event.repo.name,
+            event.repo.url
+        ])?;
+        if let Some(org) = &event.org {
+            upsert_org.execute(params![
+                org.id as i64,
+                org.login,
+                org.gravatar_id,
+                org.url,
+                org.avatar_url
+            ])?;
+        }
+
+        let payload = serde_json::to_string(&event.payload)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        upsert_event.execute(params![
+            event.id,
+            event.event_type.as_str(),
+            actor.id as i64,
+            event.repo.id as i64,
+            event.org.as_ref().map(|org| org.id as i64),
+            event.public,
+            event
+                .created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            payload
+        ])?;
+    }
+    Ok(())
+}
+
+fn sqlite_error(path: &Path, error: rusqlite::Error) -> EtlError {
+    EtlError::sink_caused_by(
+        format!(
+            "Failed to write SQLite database {}: {}",
+            path.display(),
+            error
+        ),
+        error,
+    )
+}
@@ -0,0 +1,188 @@
+use crate::EtlError;
+use crate::extract::discovery::InputDiscovery;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default time an input file must stay unchanged before it is processed in watch mode.
+pub const DEFAULT_SETTLE_TIME: Duration = Duration::from_secs(5);
+
+/// How often the folder is listed again when nothing is notified.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest time a wait goes without looking at the stop flag.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Size and modification time of a file when they were last seen to change.
+struct FileState {
+    len: u64,
+    modified: Option<SystemTime>,
+    since: Instant,
+}
+
+/// Watches the input roots and hands out each input file once it stopped changing.
+/// Change notifications (inotify on Linux) only wake the watcher up early: the inputs are
+/// listed again every time, and polled at a fixed interval when they are not available.
+/// A file that changes again after it was handed out is not picked up a second time.
+pub struct InputWatcher {
+    discovery: InputDiscovery,
+    settle: Duration,
+    notifications: Option<Notifications>,
+    pending: HashMap<PathBuf, FileState>,
+    handed_out: HashSet<PathBuf>,
+}
+
+impl InputWatcher {
+    pub fn new(discovery: InputDiscovery, settle: Duration) -> Result<Self, EtlError> {
+        // Fails early on a missing root, like a normal run.
+        discovery.list()?;
+        let notifications = match notify_changes(&discovery) {
+            Ok(notifications) => Some(notifications),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Cannot watch the inputs for changes, polling every {:?}: {}",
+                    POLL_INTERVAL, e
+                );
+                None
+            }
+        };
+        Ok(InputWatcher {
+            discovery,
+            settle,
+            notifications,
+            pending: HashMap::new(),
+            handed_out: HashSet::new(),
+        })
+    }
+
+    /// Waits until some files have been unchanged for the settle time and returns them in
+    /// input order. Returns no files once `stop` is set.
+    pub fn next_stable(&mut self, stop: &AtomicBool) -> Result<Vec<PathBuf>, EtlError> {
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(Vec::new());
+            }
+            let stable = self.scan()?;
+            if !stable.is_empty() {
+                return Ok(stable);
+            }
+            self.wait(stop);
+        }
+    }
+
+    fn scan(&mut self) -> Result<Vec<PathBuf>, EtlError> {
+        let now = Instant::now();
+        let mut stable = Vec::new();
+        for path in self.discovery.list()? {
+            if self.handed_out.contains(&path) {
+                continue;
+            }
+            // Gone again since it was listed.
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let (len, modified) = (metadata.len(), metadata.modified().ok());
+            let state = self.pending.entry(path.clone()).or_insert(FileState {
+                len,
+                modified,
+                since: now,
+            });
+            if state.len != len || state.modified != modified {
+                *state = FileState {
+                    len,
+                    modified,
+                    since: now,
+                };
+            } else if now.duration_since(state.since) >= self.settle {
+                stable.push(path);
+            }
+        }
+        for path in &stable {
+            self.pending.remove(path);
+            self.handed_out.insert(path.clone());
+        }
+        Ok(stable)
+    }
+
+    /// Sleeps until a change is notified, a pending file may have settled, or `stop` is set.
+    fn wait(&self, stop: &AtomicBool) {
+        let timeout = if self.pending.is_empty() {
+            POLL_INTERVAL
+        } else {
+            POLL_INTERVAL.min(self.settle)
+        };
+        let deadline = Instant::now() + timeout;
+        while !stop.load(Ordering::Relaxed) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return;
+            }
+            let slice = remaining.min(STOP_CHECK_INTERVAL);
+            match &self.notifications {
+                Some((_, receiver)) => {
+                    if receiver.recv_timeout(slice).is_ok() {
+                        // One listing covers a burst of changes.
+                        while receiver.try_recv().is_ok() {}
+                        return;
+                    }
+                }
+                None => thread::sleep(slice),
+            }
+        }
+    }
+}
+
+type Notifications = (
+    RecommendedWatcher,
+    mpsc::Receiver<notify::Result<notify::Event>>,
+);
+
+fn notify_changes(discovery: &InputDiscovery) -> notify::Result<Notifications> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    let mode = if discovery.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    for root in &discovery.roots {
+        watcher.watch(root, mode)?;
+    }
+    Ok((watcher, receiver))
+}
+
+/// Events per second over a sliding window, for the rolling stats of watch mode.
+pub struct RollingRate {
+    window: Duration,
+    samples: VecDeque<(Instant, usize)>,
+}
+
+impl RollingRate {
+    pub fn new(window: Duration) -> Self {
+        RollingRate {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, events: usize) {
+        let now = Instant::now();
+        self.samples.push_back((now, events));
+        while let Some((at, _)) = self.samples.front() {
+            if now.duration_since(*at) <= self.window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn per_second(&self) -> f64 {
+        let events: usize = self.samples.iter().map(|(_, events)| events).sum();
+        events as f64 / self.window.as_secs_f64()
+    }
+}
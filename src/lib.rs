@@ -1,22 +1,154 @@
+pub mod error;
 pub mod extract;
 pub mod model;
 
+pub use error::EtlError;
+pub use extract::compression::Compression;
+pub use extract::discovery::InputOrder;
+pub use extract::partition::PartitionKey;
+pub use extract::sink::OutputFormat;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// Default number of events allowed in flight between readers and sinks in streaming mode.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 10_000;
+
+/// Default number of rows per Parquet row group.
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 128 * 1024;
+
+/// Process exit codes, one per failure class, so schedulers can tell failures apart.
+pub const EXIT_FATAL: i32 = 1;
+pub const EXIT_IO_ERROR: i32 = 3;
+pub const EXIT_DATA_ERROR: i32 = 4;
+
 pub struct Config {
     pub path_to_data: String,
+    /// Further input roots, listed after `path_to_data`.
+    pub extra_paths: Vec<String>,
+    /// File listing further input roots, one per line.
+    pub manifest: Option<String>,
+    pub recursive: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub input_order: InputOrder,
     pub dry_run: bool,
     pub show_stats: bool,
-    pub event_type_filter: Option<String>,
+    pub event_type_filter: Vec<String>,
+    pub exclude_event_type_filter: Vec<String>,
+    pub actor_filter: Vec<String>,
+    pub repo_filter: Vec<String>,
+    pub org_filter: Vec<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub where_clause: Option<String>,
     pub output_file: Option<String>,
+    pub output_format: OutputFormat,
+    pub columns: Vec<String>,
+    pub partition_by: Vec<PartitionKey>,
+    pub max_part_events: Option<usize>,
+    pub max_part_bytes: Option<u64>,
+    pub compress: Option<Compression>,
+    pub compress_level: Option<i32>,
+    pub row_group_size: usize,
+    pub parquet_compression: String,
+    pub reject_file: Option<String>,
     pub quiet_mode: bool,
+    pub streaming: bool,
+    pub max_in_flight: usize,
+    pub max_errors: Option<usize>,
+    pub max_error_rate: Option<f64>,
+    pub fail_fast: bool,
+    pub checkpoint_file: Option<String>,
+    pub resume: bool,
+    pub incremental_state: Option<String>,
+    pub watch: bool,
+    /// Time an input must stay unchanged before a watching run processes it.
+    pub watch_settle: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            path_to_data: String::new(),
+            extra_paths: Vec::new(),
+            manifest: None,
+            recursive: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            input_order: InputOrder::default(),
+            dry_run: false,
+            show_stats: false,
+            event_type_filter: Vec::new(),
+            exclude_event_type_filter: Vec::new(),
+            actor_filter: Vec::new(),
+            repo_filter: Vec::new(),
+            org_filter: Vec::new(),
+            since: None,
+            until: None,
+            where_clause: None,
+            output_file: None,
+            output_format: OutputFormat::default(),
+            columns: Vec::new(),
+            partition_by: Vec::new(),
+            max_part_events: None,
+            max_part_bytes: None,
+            compress: None,
+            compress_level: None,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            parquet_compression: "snappy".to_string(),
+            reject_file: None,
+            quiet_mode: false,
+            streaming: false,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            max_errors: None,
+            max_error_rate: None,
+            fail_fast: false,
+            checkpoint_file: None,
+            resume: false,
+            incremental_state: None,
+            watch: false,
+            watch_settle: extract::watch::DEFAULT_SETTLE_TIME,
+        }
+    }
+}
+
+/// Outcome of a run that got as far as reading its inputs.
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub files: usize,
+    /// Inputs left out because a checkpoint or incremental state shows an earlier run did them.
+    pub skipped_files: usize,
+    pub failed_files: usize,
+    pub lines: usize,
+    pub events: usize,
+    pub rejected: usize,
+    pub error_budget_exceeded: bool,
+    pub aborted: bool,
+}
+
+impl RunSummary {
+    /// A blown error budget (corrupt input) takes precedence over unreadable files.
+    pub fn exit_code(&self) -> i32 {
+        if self.error_budget_exceeded {
+            EXIT_DATA_ERROR
+        } else if self.failed_files > 0 {
+            EXIT_IO_ERROR
+        } else {
+            0
+        }
+    }
+}
+
+/// Runs the ETL once over the inputs; a watching run never returns on its own, see [`run_until`].
+pub fn run(config: Config) -> Result<RunSummary, EtlError> {
+    run_until(config, &AtomicBool::new(false))
 }
 
-pub fn run(config: Config) -> Result<(), String> {
-    crate::extract::json_lines::check_folder(
-        &config.path_to_data,
-        config.dry_run,
-        config.show_stats,
-        config.event_type_filter,
-        config.output_file,
-        config.quiet_mode,
-    )
+/// Like [`run`], but a watching run stops and finishes its outputs once `stop` is set.
+pub fn run_until(config: Config, stop: &AtomicBool) -> Result<RunSummary, EtlError> {
+    if config.watch {
+        crate::extract::json_lines::watch(&config, stop)
+    } else {
+        crate::extract::json_lines::process(&config)
+    }
 }
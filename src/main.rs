@@ -1,17 +1,67 @@
 // main.rs
-use clap::Parser;
-use etl_rust::{Config, run};
-use std::time::Instant;
+use clap::{ArgGroup, Parser};
+use etl_rust::extract::watch::DEFAULT_SETTLE_TIME;
+use etl_rust::{
+    Compression, Config, DEFAULT_MAX_IN_FLIGHT, DEFAULT_ROW_GROUP_SIZE, EXIT_FATAL, InputOrder,
+    OutputFormat, PartitionKey, run_until,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Set by SIGINT/SIGTERM to let a watching run finish its outputs and exit.
+static STOP: AtomicBool = AtomicBool::new(false);
 
 #[derive(Parser)]
 #[command(version, about = "ETL tool for processing JSON lines")]
+#[command(group(ArgGroup::new("input").required(true).multiple(true).args(["inputs", "path", "manifest"])))]
 struct Cli {
-    #[arg(short, long)]
-    path: String,
+    #[arg(
+        value_name = "INPUT",
+        help = "Input folders or files, or - to read the standard input"
+    )]
+    inputs: Vec<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Input folder or file; repeat to read several roots"
+    )]
+    path: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Also read the folders and files listed in FILE, one per line"
+    )]
+    manifest: Option<String>,
+
+    #[arg(short, long, help = "Also read the subfolders of the input folders")]
+    recursive: bool,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Only read input files matching GLOB: a file name pattern, or a path relative to its root when it contains / (e.g. 2024/01/**/*.json.gz); repeatable"
+    )]
+    include: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Skip input files matching GLOB, written like --include; repeatable"
+    )]
+    exclude: Vec<String>,
 
     #[arg(short, long)]
     show_time: bool,
 
+    #[arg(
+        long,
+        default_value_t = InputOrder::Time,
+        help = "Order in which input files are read: time (GH Archive hours chronologically, then other files by name), name (natural order), size (smallest first) or none (folder order)"
+    )]
+    order: InputOrder,
+
     #[arg(long)]
     dry_run: bool,
 
@@ -20,34 +70,276 @@ struct Cli {
 
     #[arg(
         long,
-        help = "Filter by event type (e.g., PushEvent, PullRequestEvent)"
+        value_delimiter = ',',
+        help = "Filter by event type (e.g., PushEvent,PullRequestEvent); comma list or repeated flag"
     )]
-    event_type: Option<String>,
+    event_type: Vec<String>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Drop events of these types (e.g., WatchEvent); comma list or repeated flag"
+    )]
+    exclude_event_type: Vec<String>,
+
+    #[arg(
+        long = "actor",
+        value_name = "LOGIN",
+        help = "Filter by actor login, case-insensitive (repeat to match any of several actors)"
+    )]
+    actors: Vec<String>,
+
+    #[arg(
+        long = "repo",
+        value_name = "PATTERN",
+        help = "Filter by repository name: exact (owner/name), glob (rust-lang/*) or regex (re:^apache/); repeatable"
+    )]
+    repos: Vec<String>,
+
+    #[arg(
+        long = "org",
+        value_name = "PATTERN",
+        help = "Filter by organization login: exact, glob or regex (re:...); repeatable"
+    )]
+    orgs: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "TIME",
+        allow_hyphen_values = true,
+        help = "Keep events created at or after TIME (RFC 3339, or relative like -6h, -2d)"
+    )]
+    since: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "TIME",
+        allow_hyphen_values = true,
+        help = "Keep events created before TIME (RFC 3339, or relative like -30m)"
+    )]
+    until: Option<String>,
+
+    #[arg(
+        long = "where",
+        value_name = "EXPR",
+        help = "Keep events matching an expression, e.g. 'type == \"IssuesEvent\" && payload.action == \"opened\" && repo.name ~ \"^apache/\"'"
+    )]
+    where_clause: Option<String>,
 
     #[arg(short, long, help = "Output file path for results")]
     output: Option<String>,
 
+    #[arg(
+        long,
+        default_value_t = OutputFormat::JsonLines,
+        help = "Output file format: jsonl, parquet, csv, tsv or sqlite (upserts into an existing database)"
+    )]
+    format: OutputFormat,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Columns of csv/tsv output as dotted paths (e.g. id,type,actor.login,payload.action)"
+    )]
+    columns: Vec<String>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Write the output as a directory partitioned by these keys: type, date, hour, org, repo_owner"
+    )]
+    partition_by: Vec<PartitionKey>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Start a new part file of a partition after N events"
+    )]
+    max_part_events: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Start a new part file of a partition once it reaches BYTES bytes"
+    )]
+    max_part_bytes: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "CODEC",
+        help = "Compress the output with gzip, zstd or bzip2 (default: from the output extension)"
+    )]
+    compress: Option<Compression>,
+
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        help = "Compression level of the output (gzip 0-9, zstd 1-22, bzip2 1-9)"
+    )]
+    compress_level: Option<i32>,
+
+    #[arg(
+        long,
+        default_value_t = DEFAULT_ROW_GROUP_SIZE,
+        help = "Rows per row group for Parquet output"
+    )]
+    row_group_size: usize,
+
+    #[arg(
+        long,
+        default_value = "snappy",
+        help = "Parquet column compression: none, snappy, gzip[:LEVEL] or zstd[:LEVEL]"
+    )]
+    parquet_compression: String,
+
+    #[arg(
+        long,
+        help = "Write unparseable lines with source file, line number and error as JSON lines"
+    )]
+    reject_file: Option<String>,
+
     #[arg(long, help = "Quiet Mode (suppressing output)")]
     quiet: bool,
+
+    #[arg(
+        long,
+        help = "Stream events from readers to the output as each file is read, holding one file per reader in memory (output order across files is not preserved)"
+    )]
+    stream: bool,
+
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_IN_FLIGHT,
+        help = "Approximate number of events buffered between readers and the output in streaming mode"
+    )]
+    max_in_flight: usize,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Fail the run (exit code 4) when more than N lines cannot be parsed"
+    )]
+    max_errors: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "RATE",
+        help = "Fail the run (exit code 4) when the share of unparseable lines exceeds RATE, e.g. 0.01"
+    )]
+    max_error_rate: Option<f64>,
+
+    #[arg(
+        long,
+        help = "Stop reading as soon as the error budget is exceeded instead of finishing the run"
+    )]
+    fail_fast: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Record input files in FILE as their events are committed to the outputs, which are then written in place"
+    )]
+    checkpoint: Option<String>,
+
+    #[arg(
+        long,
+        requires = "checkpoint",
+        help = "Skip the input files recorded in the checkpoint and append to the outputs"
+    )]
+    resume: bool,
+
+    #[arg(
+        long,
+        value_name = "STATE_FILE",
+        conflicts_with = "checkpoint",
+        help = "Only process GH Archive hours newer than the last run recorded in STATE_FILE and append them to the outputs"
+    )]
+    incremental: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "dry_run",
+        help = "Keep running and process new input files as they appear, until SIGINT or SIGTERM"
+    )]
+    watch: bool,
+
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = DEFAULT_SETTLE_TIME.as_secs(),
+        requires = "watch",
+        help = "Seconds an input file must stay unchanged before it is processed in watch mode"
+    )]
+    settle: u64,
 }
 
 fn main() {
     let cli = Cli::parse();
+    let mut paths = cli.path.into_iter().chain(cli.inputs);
     let config = Config {
-        path_to_data: cli.path,
+        path_to_data: paths.next().unwrap_or_default(),
+        extra_paths: paths.collect(),
+        manifest: cli.manifest,
+        recursive: cli.recursive,
+        include: cli.include,
+        exclude: cli.exclude,
+        input_order: cli.order,
         dry_run: cli.dry_run,
         show_stats: cli.stats,
         event_type_filter: cli.event_type,
+        exclude_event_type_filter: cli.exclude_event_type,
+        actor_filter: cli.actors,
+        repo_filter: cli.repos,
+        org_filter: cli.orgs,
+        since: cli.since,
+        until: cli.until,
+        where_clause: cli.where_clause,
         output_file: cli.output,
+        output_format: cli.format,
+        columns: cli.columns,
+        partition_by: cli.partition_by,
+        max_part_events: cli.max_part_events,
+        max_part_bytes: cli.max_part_bytes,
+        compress: cli.compress,
+        compress_level: cli.compress_level,
+        row_group_size: cli.row_group_size,
+        parquet_compression: cli.parquet_compression,
+        reject_file: cli.reject_file,
         quiet_mode: cli.quiet,
+        streaming: cli.stream,
+        max_in_flight: cli.max_in_flight,
+        max_errors: cli.max_errors,
+        max_error_rate: cli.max_error_rate,
+        fail_fast: cli.fail_fast,
+        checkpoint_file: cli.checkpoint,
+        resume: cli.resume,
+        incremental_state: cli.incremental,
+        watch: cli.watch,
+        watch_settle: Duration::from_secs(cli.settle),
     };
 
-    let start = Instant::now();
-    if let Err(e) = run(config) {
-        eprintln!("Fatal error: {}", e);
-        std::process::exit(1);
+    if cli.watch
+        && let Err(e) = ctrlc::set_handler(|| {
+            // A second signal does not wait for the outputs.
+            if STOP.swap(true, Ordering::SeqCst) {
+                std::process::exit(EXIT_FATAL);
+            }
+        })
+    {
+        eprintln!("Fatal error: Cannot install signal handler: {}", e);
+        std::process::exit(EXIT_FATAL);
     }
+
+    let start = Instant::now();
+    let summary = match run_until(config, &STOP) {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("Fatal error: {}", e);
+            std::process::exit(e.exit_code());
+        }
+    };
     if cli.show_time {
         println!("⏱️ Time: {:.2?}", start.elapsed());
     }
+    std::process::exit(summary.exit_code());
 }
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ActorRef<'a> {
+    pub id: u64,
+    #[serde(borrow)]
+    pub login: Cow<'a, str>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RepoRef<'a> {
+    pub id: u64,
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct OrgRef<'a> {
+    pub id: u64,
+    #[serde(borrow)]
+    pub login: Cow<'a, str>,
+}
+
+/// The payload fields filters and stats look at; the rest is skipped.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PayloadRef<'a> {
+    #[serde(borrow)]
+    pub action: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub ref_type: Option<Cow<'a, str>>,
+    #[serde(borrow, rename = "ref")]
+    pub r#ref: Option<Cow<'a, str>>,
+    pub number: Option<u32>,
+}
+
+/// Borrowed view of a [`GitHubEvent`](crate::model::github::GitHubEvent) for read-heavy
+/// paths that do not keep events around. Strings point into the parsed line whenever they
+/// need no unescaping.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GitHubEventRef<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    #[serde(borrow, rename = "type")]
+    pub event_type: Cow<'a, str>,
+    #[serde(borrow)]
+    pub actor: ActorRef<'a>,
+    #[serde(borrow)]
+    pub repo: RepoRef<'a>,
+    #[serde(borrow)]
+    pub payload: PayloadRef<'a>,
+    pub public: bool,
+    pub created_at: DateTime<Utc>,
+    #[serde(borrow)]
+    pub org: Option<OrgRef<'a>>,
+}
@@ -0,0 +1,480 @@
+use crate::model::github::GitHubEvent;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    String,
+    Number,
+    Bool,
+    Timestamp,
+    /// Untyped JSON coming from `serde_json::Value` payload members; checked at runtime.
+    Any,
+}
+
+impl FieldType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::String => "string",
+            FieldType::Number => "number",
+            FieldType::Bool => "bool",
+            FieldType::Timestamp => "timestamp",
+            FieldType::Any => "any",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(Cow<'a, str>),
+    Timestamp(DateTime<Utc>),
+}
+
+impl FieldValue<'_> {
+    /// Compares two values of the same kind; values of different kinds are unordered.
+    pub fn compare(&self, other: &FieldValue<'_>) -> Option<Ordering> {
+        match (self, other) {
+            (FieldValue::Null, FieldValue::Null) => Some(Ordering::Equal),
+            (FieldValue::Bool(a), FieldValue::Bool(b)) => Some(a.cmp(b)),
+            (FieldValue::Number(a), FieldValue::Number(b)) => a.partial_cmp(b),
+            (FieldValue::Str(a), FieldValue::Str(b)) => Some(a.as_ref().cmp(b.as_ref())),
+            (FieldValue::Timestamp(a), FieldValue::Timestamp(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    pub fn into_owned(self) -> FieldValue<'static> {
+        match self {
+            FieldValue::Null => FieldValue::Null,
+            FieldValue::Bool(b) => FieldValue::Bool(b),
+            FieldValue::Number(n) => FieldValue::Number(n),
+            FieldValue::Str(s) => FieldValue::Str(Cow::Owned(s.into_owned())),
+            FieldValue::Timestamp(t) => FieldValue::Timestamp(t),
+        }
+    }
+
+    /// Text form used for flat outputs; `None` for null.
+    pub fn to_text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            FieldValue::Null => None,
+            FieldValue::Bool(b) => Some(Cow::Owned(b.to_string())),
+            FieldValue::Number(n) => Some(Cow::Owned(n.to_string())),
+            FieldValue::Str(s) => Some(Cow::Borrowed(s.as_ref())),
+            FieldValue::Timestamp(t) => Some(Cow::Owned(
+                t.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+            )),
+        }
+    }
+}
+
+/// Reads one field of an event; the variant gives the type of the field.
+#[derive(Debug, Clone, Copy)]
+enum Accessor {
+    Str(for<'a> fn(&'a GitHubEvent) -> Option<&'a str>),
+    Number(fn(&GitHubEvent) -> Option<u64>),
+    Bool(fn(&GitHubEvent) -> Option<bool>),
+    Timestamp(fn(&GitHubEvent) -> Option<DateTime<Utc>>),
+    /// A free-form `serde_json::Value` member; paths below it are looked up at runtime.
+    Json(for<'a> fn(&'a GitHubEvent) -> Option<&'a Value>),
+    /// A free-form list member, read whole as JSON text or one item at a time.
+    JsonItems(for<'a> fn(&'a GitHubEvent) -> Option<&'a [Value]>),
+}
+
+impl Accessor {
+    fn field_type(&self) -> FieldType {
+        match self {
+            Accessor::Str(_) => FieldType::String,
+            Accessor::Number(_) => FieldType::Number,
+            Accessor::Bool(_) => FieldType::Bool,
+            Accessor::Timestamp(_) => FieldType::Timestamp,
+            Accessor::Json(_) | Accessor::JsonItems(_) => FieldType::Any,
+        }
+    }
+}
+
+/// Fields of the model. Paths below a free-form member are looked up in its JSON.
+const FIELDS: &[(&str, Accessor)] = &[
+    ("id", Accessor::Str(|e| Some(&e.id))),
+    ("type", Accessor::Str(|e| Some(e.event_type.as_str()))),
+    ("public", Accessor::Bool(|e| Some(e.public))),
+    ("created_at", Accessor::Timestamp(|e| Some(e.created_at))),
+    ("actor.id", Accessor::Number(|e| Some(e.actor.id))),
+    ("actor.login", Accessor::Str(|e| Some(&e.actor.login))),
+    (
+        "actor.display_login",
+        Accessor::Str(|e| e.actor.display_login.as_deref()),
+    ),
+    (
+        "actor.gravatar_id",
+        Accessor::Str(|e| Some(&e.actor.gravatar_id)),
+    ),
+    ("actor.url", Accessor::Str(|e| Some(&e.actor.url))),
+    (
+        "actor.avatar_url",
+        Accessor::Str(|e| Some(&e.actor.avatar_url)),
+    ),
+    ("repo.id", Accessor::Number(|e| Some(e.repo.id))),
+    ("repo.name", Accessor::Str(|e| Some(&e.repo.name))),
+    ("repo.url", Accessor::Str(|e| Some(&e.repo.url))),
+    ("org.id", Accessor::Number(|e| Some(e.org.as_ref()?.id))),
+    ("org.login", Accessor::Str(|e| Some(&e.org.as_ref()?.login))),
+    (
+        "org.gravatar_id",
+        Accessor::Str(|e| Some(&e.org.as_ref()?.gravatar_id)),
+    ),
+    ("org.url", Accessor::Str(|e| Some(&e.org.as_ref()?.url))),
+    (
+        "org.avatar_url",
+        Accessor::Str(|e| Some(&e.org.as_ref()?.avatar_url)),
+    ),
+    (
+        "payload.action",
+        Accessor::Str(|e| e.payload.action.as_deref()),
+    ),
+    (
+        "payload.ref_type",
+        Accessor::Str(|e| e.payload.ref_type.as_deref()),
+    ),
+    ("payload.ref", Accessor::Str(|e| e.payload.r#ref.as_deref())),
+    (
+        "payload.full_ref",
+        Accessor::Str(|e| e.payload.full_ref.as_deref()),
+    ),
+    (
+        "payload.pusher_type",
+        Accessor::Str(|e| e.payload.pusher_type.as_deref()),
+    ),
+    (
+        "payload.master_branch",
+        Accessor::Str(|e| e.payload.master_branch.as_deref()),
+    ),
+    (
+        "payload.description",
+        Accessor::Str(|e| e.payload.description.as_deref()),
+    ),
+    (
+        "payload.repository_id",
+        Accessor::Number(|e| e.payload.repository_id),
+    ),
+    ("payload.push_id", Accessor::Number(|e| e.payload.push_id)),
+    ("payload.head", Accessor::Str(|e| e.payload.head.as_deref())),
+    (
+        "payload.before",
+        Accessor::Str(|e| e.payload.before.as_deref()),
+    ),
+    (
+        "payload.number",
+        Accessor::Number(|e| e.payload.number.map(u64::from)),
+    ),
+    (
+        "payload.pull_request.url",
+        Accessor::Str(|e| e.payload.pull_request.as_ref()?.url.as_deref()),
+    ),
+    (
+        "payload.pull_request.id",
+        Accessor::Number(|e| e.payload.pull_request.as_ref()?.id),
+    ),
+    (
+        "payload.pull_request.number",
+        Accessor::Number(|e| e.payload.pull_request.as_ref()?.number.map(u64::from)),
+    ),
+    (
+        "payload.pull_request.head",
+        Accessor::Json(|e| e.payload.pull_request.as_ref()?.head.as_ref()),
+    ),
+    (
+        "payload.pull_request.base",
+        Accessor::Json(|e| e.payload.pull_request.as_ref()?.base.as_ref()),
+    ),
+    (
+        "payload.issue.url",
+        Accessor::Str(|e| e.payload.issue.as_ref()?.url.as_deref()),
+    ),
+    (
+        "payload.issue.id",
+        Accessor::Number(|e| e.payload.issue.as_ref()?.id),
+    ),
+    (
+        "payload.issue.number",
+        Accessor::Number(|e| e.payload.issue.as_ref()?.number.map(u64::from)),
+    ),
+    (
+        "payload.issue.title",
+        Accessor::Str(|e| e.payload.issue.as_ref()?.title.as_deref()),
+    ),
+    (
+        "payload.issue.body",
+        Accessor::Str(|e| e.payload.issue.as_ref()?.body.as_deref()),
+    ),
+    (
+        "payload.issue.user",
+        Accessor::Json(|e| e.payload.issue.as_ref()?.user.as_ref()),
+    ),
+    (
+        "payload.issue.state",
+        Accessor::Str(|e| e.payload.issue.as_ref()?.state.as_deref()),
+    ),
+    (
+        "payload.issue.assignee",
+        Accessor::Json(|e| e.payload.issue.as_ref()?.assignee.as_ref()),
+    ),
+    (
+        "payload.issue.assignees",
+        Accessor::JsonItems(|e| e.payload.issue.as_ref()?.assignees.as_deref()),
+    ),
+    (
+        "payload.issue.labels",
+        Accessor::JsonItems(|e| e.payload.issue.as_ref()?.labels.as_deref()),
+    ),
+    (
+        "payload.comment.url",
+        Accessor::Str(|e| e.payload.comment.as_ref()?.url.as_deref()),
+    ),
+    (
+        "payload.comment.id",
+        Accessor::Number(|e| e.payload.comment.as_ref()?.id),
+    ),
+    (
+        "payload.comment.body",
+        Accessor::Str(|e| e.payload.comment.as_ref()?.body.as_deref()),
+    ),
+    (
+        "payload.comment.user",
+        Accessor::Json(|e| e.payload.comment.as_ref()?.user.as_ref()),
+    ),
+    (
+        "payload.comment.created_at",
+        Accessor::Str(|e| e.payload.comment.as_ref()?.created_at.as_deref()),
+    ),
+    (
+        "payload.comment.updated_at",
+        Accessor::Str(|e| e.payload.comment.as_ref()?.updated_at.as_deref()),
+    ),
+    (
+        "payload.review.id",
+        Accessor::Number(|e| e.payload.review.as_ref()?.id),
+    ),
+    (
+        "payload.review.user",
+        Accessor::Json(|e| e.payload.review.as_ref()?.user.as_ref()),
+    ),
+    (
+        "payload.review.body",
+        Accessor::Str(|e| e.payload.review.as_ref()?.body.as_deref()),
+    ),
+    (
+        "payload.review.state",
+        Accessor::Str(|e| e.payload.review.as_ref()?.state.as_deref()),
+    ),
+    (
+        "payload.review.submitted_at",
+        Accessor::Str(|e| e.payload.review.as_ref()?.submitted_at.as_deref()),
+    ),
+    (
+        "payload.release.id",
+        Accessor::Number(|e| e.payload.release.as_ref()?.id),
+    ),
+    (
+        "payload.release.tag_name",
+        Accessor::Str(|e| e.payload.release.as_ref()?.tag_name.as_deref()),
+    ),
+    (
+        "payload.release.name",
+        Accessor::Str(|e| e.payload.release.as_ref()?.name.as_deref()),
+    ),
+    (
+        "payload.release.body",
+        Accessor::Str(|e| e.payload.release.as_ref()?.body.as_deref()),
+    ),
+    (
+        "payload.release.draft",
+        Accessor::Bool(|e| e.payload.release.as_ref()?.draft),
+    ),
+    (
+        "payload.release.prerelease",
+        Accessor::Bool(|e| e.payload.release.as_ref()?.prerelease),
+    ),
+    (
+        "payload.release.created_at",
+        Accessor::Str(|e| e.payload.release.as_ref()?.created_at.as_deref()),
+    ),
+    (
+        "payload.forkee.id",
+        Accessor::Number(|e| e.payload.forkee.as_ref()?.id),
+    ),
+    (
+        "payload.forkee.name",
+        Accessor::Str(|e| e.payload.forkee.as_ref()?.name.as_deref()),
+    ),
+    (
+        "payload.forkee.full_name",
+        Accessor::Str(|e| e.payload.forkee.as_ref()?.full_name.as_deref()),
+    ),
+    (
+        "payload.forkee.owner",
+        Accessor::Json(|e| e.payload.forkee.as_ref()?.owner.as_ref()),
+    ),
+    (
+        "payload.forkee.description",
+        Accessor::Str(|e| e.payload.forkee.as_ref()?.description.as_deref()),
+    ),
+    (
+        "payload.forkee.url",
+        Accessor::Str(|e| e.payload.forkee.as_ref()?.url.as_deref()),
+    ),
+    (
+        "payload.label.id",
+        Accessor::Number(|e| e.payload.label.as_ref()?.id),
+    ),
+    (
+        "payload.label.name",
+        Accessor::Str(|e| e.payload.label.as_ref()?.name.as_deref()),
+    ),
+    (
+        "payload.label.color",
+        Accessor::Str(|e| e.payload.label.as_ref()?.color.as_deref()),
+    ),
+    (
+        "payload.label.default",
+        Accessor::Bool(|e| e.payload.label.as_ref()?.default),
+    ),
+    (
+        "payload.assignee",
+        Accessor::Json(|e| e.payload.assignee.as_ref()),
+    ),
+    (
+        "payload.assignees",
+        Accessor::JsonItems(|e| e.payload.assignees.as_deref()),
+    ),
+    (
+        "payload.labels",
+        Accessor::JsonItems(|e| e.payload.labels.as_deref()),
+    ),
+    (
+        "payload.member",
+        Accessor::Json(|e| e.payload.member.as_ref()),
+    ),
+    (
+        "payload.pages",
+        Accessor::JsonItems(|e| e.payload.pages.as_deref()),
+    ),
+    (
+        "payload.discussion",
+        Accessor::Json(|e| e.payload.discussion.as_ref()),
+    ),
+];
+
+/// Type of a dotted path, or `None` when the model has no such field.
+pub fn field_type(path: &str) -> Option<FieldType> {
+    Field::resolve(path).map(|field| field.field_type())
+}
+
+pub fn known_fields() -> impl Iterator<Item = &'static str> {
+    FIELDS.iter().map(|(name, _)| *name)
+}
+
+/// Reads a field from an event. Missing optional values (no org, absent payload member) are `Null`.
+/// The path is expected to be valid according to [`field_type`].
+pub fn lookup<'a>(event: &'a GitHubEvent, path: &str) -> FieldValue<'a> {
+    Field::resolve(path).map_or(FieldValue::Null, |field| field.read(event))
+}
+
+/// A field path resolved once, to read the same field from many events.
+#[derive(Debug, Clone)]
+pub struct Field {
+    path: String,
+    accessor: Accessor,
+    /// The path below a free-form member.
+    rest: Option<String>,
+}
+
+impl Field {
+    /// `None` when the model has no such field.
+    pub fn resolve(path: &str) -> Option<Field> {
+        FIELDS.iter().find_map(|(name, accessor)| {
+            let rest = match path.strip_prefix(name)? {
+                "" => None,
+                rest if accessor.field_type() == FieldType::Any => {
+                    Some(rest.strip_prefix('.').filter(|rest| !rest.is_empty())?)
+                }
+                _ => return None,
+            };
+            Some(Field {
+                path: path.to_string(),
+                accessor: *accessor,
+                rest: rest.map(str::to_string),
+            })
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn field_type(&self) -> FieldType {
+        self.accessor.field_type()
+    }
+
+    pub fn read<'a>(&self, event: &'a GitHubEvent) -> FieldValue<'a> {
+        let value = match self.accessor {
+            Accessor::Str(read) => read(event).map(|s| FieldValue::Str(Cow::Borrowed(s))),
+            Accessor::Number(read) => read(event).map(|n| FieldValue::Number(n as f64)),
+            Accessor::Bool(read) => read(event).map(FieldValue::Bool),
+            Accessor::Timestamp(read) => read(event).map(FieldValue::Timestamp),
+            Accessor::Json(read) => return json_member(read(event), self.rest.as_deref()),
+            Accessor::JsonItems(read) => return json_items(read(event), self.rest.as_deref()),
+        };
+        value.unwrap_or(FieldValue::Null)
+    }
+}
+
+/// A free-form member, or the value at `rest` below it.
+fn json_member<'a>(value: Option<&'a Value>, rest: Option<&str>) -> FieldValue<'a> {
+    let value = match (value, rest) {
+        (Some(value), None) => Some(value),
+        (Some(value), Some(rest)) => json_lookup(value, rest),
+        (None, _) => None,
+    };
+    value.map_or(FieldValue::Null, json_to_field)
+}
+
+/// A free-form list member as JSON text, or the value at `rest` below one of its items.
+fn json_items<'a>(items: Option<&'a [Value]>, rest: Option<&str>) -> FieldValue<'a> {
+    let Some(items) = items else {
+        return FieldValue::Null;
+    };
+    let Some(rest) = rest else {
+        return serde_json::to_string(items)
+            .map_or(FieldValue::Null, |json| FieldValue::Str(Cow::Owned(json)));
+    };
+    let (index, rest) = match rest.split_once('.') {
+        Some((index, rest)) => (index, Some(rest)),
+        None => (rest, None),
+    };
+    let item = index.parse::<usize>().ok().and_then(|i| items.get(i));
+    json_member(item, rest)
+}
+
+fn json_lookup<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .try_fold(value, |current, segment| match current {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+fn json_to_field(value: &Value) -> FieldValue<'_> {
+    match value {
+        Value::Null => FieldValue::Null,
+        Value::Bool(b) => FieldValue::Bool(*b),
+        Value::Number(n) => n.as_f64().map_or(FieldValue::Null, FieldValue::Number),
+        Value::String(s) => FieldValue::Str(Cow::Borrowed(s)),
+        other => FieldValue::Str(Cow::Owned(other.to_string())),
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[allow(dead_code)]
@@ -46,6 +47,33 @@ pub enum EventType {
     PublicEvent,
     CommitCommentEvent,
     DiscussionEvent,
+    /// Any event type this model does not know yet, kept under its original name.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventType::PushEvent => "PushEvent",
+            EventType::PullRequestEvent => "PullRequestEvent",
+            EventType::PullRequestReviewEvent => "PullRequestReviewEvent",
+            EventType::PullRequestReviewCommentEvent => "PullRequestReviewCommentEvent",
+            EventType::CreateEvent => "CreateEvent",
+            EventType::DeleteEvent => "DeleteEvent",
+            EventType::IssuesEvent => "IssuesEvent",
+            EventType::IssueCommentEvent => "IssueCommentEvent",
+            EventType::WatchEvent => "WatchEvent",
+            EventType::ForkEvent => "ForkEvent",
+            EventType::ReleaseEvent => "ReleaseEvent",
+            EventType::GollumEvent => "GollumEvent",
+            EventType::MemberEvent => "MemberEvent",
+            EventType::PublicEvent => "PublicEvent",
+            EventType::CommitCommentEvent => "CommitCommentEvent",
+            EventType::DiscussionEvent => "DiscussionEvent",
+            EventType::Other(name) => name,
+        }
+    }
 }
 #[allow(dead_code)]
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -157,7 +185,7 @@ pub struct GitHubEvent {
     pub repo: Repo,
     pub payload: GitHubPayload,
     pub public: bool,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
     pub org: Option<Org>,
 }
 
@@ -1 +1,3 @@
+pub mod borrowed;
+pub mod fields;
 pub mod github;
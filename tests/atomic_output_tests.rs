@@ -0,0 +1,186 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{Setup, event};
+    use etl_rust::extract::atomic::AtomicFile;
+    use etl_rust::extract::compression::OutputCompression;
+    use etl_rust::extract::sink::{EventSink, JsonLinesSink};
+    use etl_rust::{Config, OutputFormat, PartitionKey, run};
+    use std::fs;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    fn temp_files(dir: &Path) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                found.extend(temp_files(&path));
+            } else if path.to_str().unwrap().ends_with(".tmp") {
+                found.push(path);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn successful_run_replaces_output() {
+        let s = Setup::new("out.jsonl");
+        s.write("events.json", &[1, 2]);
+        fs::write(&s.output, "previous\n").unwrap();
+
+        run(s.config()).unwrap();
+
+        let content = fs::read_to_string(&s.output).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("previous"));
+        assert!(temp_files(s.tmp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn dry_run_leaves_output_untouched() {
+        let s = Setup::new("out.jsonl");
+        s.write("events.json", &[1]);
+        fs::write(&s.output, "previous\n").unwrap();
+
+        run(Config {
+            dry_run: true,
+            ..s.config()
+        })
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&s.output).unwrap(), "previous\n");
+    }
+
+    #[test]
+    fn invalid_options_leave_output_untouched() {
+        let s = Setup::new("out.csv");
+        s.write("events.json", &[1]);
+        fs::write(&s.output, "previous\n").unwrap();
+
+        let result = run(Config {
+            output_format: OutputFormat::Csv,
+            columns: vec!["no.such.column".to_string()],
+            ..s.config()
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&s.output).unwrap(), "previous\n");
+        assert!(temp_files(s.tmp_dir.path()).is_empty());
+    }
+
+    fn aborted_run_keeps_previous_output(streaming: bool) {
+        let s = Setup::new("out.jsonl");
+        s.write_lines(
+            "events.json",
+            &[event(1), "{broken".to_string(), "{broken".to_string()],
+        );
+        let reject_file = s.path("rejected.jsonl");
+        fs::write(&s.output, "previous\n").unwrap();
+
+        let summary = run(Config {
+            reject_file: Some(reject_file.to_str().unwrap().to_string()),
+            max_errors: Some(1),
+            fail_fast: true,
+            streaming,
+            ..s.config()
+        })
+        .unwrap();
+
+        assert!(summary.aborted);
+        assert_eq!(fs::read_to_string(&s.output).unwrap(), "previous\n");
+        // The rejected lines are still written for inspection.
+        assert_eq!(fs::read_to_string(&reject_file).unwrap().lines().count(), 2);
+        assert!(temp_files(s.tmp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn aborted_run_keeps_previous_output_normal() {
+        aborted_run_keeps_previous_output(false);
+    }
+
+    #[test]
+    fn aborted_run_keeps_previous_output_streaming() {
+        aborted_run_keeps_previous_output(true);
+    }
+
+    #[test]
+    fn aborted_partitioned_run_keeps_previous_parts() {
+        let s = Setup::new("out");
+        s.write_lines(
+            "events.json",
+            &[event(1), "{broken".to_string(), "{broken".to_string()],
+        );
+        let part = s.output.join("type=PushEvent").join("part-0000.jsonl");
+        fs::create_dir_all(part.parent().unwrap()).unwrap();
+        fs::write(&part, "previous\n").unwrap();
+
+        run(Config {
+            partition_by: vec![PartitionKey::Type],
+            max_errors: Some(1),
+            fail_fast: true,
+            streaming: true,
+            ..s.config()
+        })
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&part).unwrap(), "previous\n");
+        assert!(temp_files(s.tmp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn unfinished_sink_leaves_no_file() {
+        let tmp_dir = tempdir().unwrap();
+        let output = tmp_dir.path().join("out.jsonl");
+
+        let sink = JsonLinesSink::create_compressed(
+            output.to_str().unwrap(),
+            OutputCompression::from_path(&output),
+        )
+        .unwrap();
+        assert_eq!(temp_files(tmp_dir.path()).len(), 1);
+        drop(sink);
+
+        assert!(!output.exists());
+        assert!(temp_files(tmp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn finished_sink_commits() {
+        let tmp_dir = tempdir().unwrap();
+        let output = tmp_dir.path().join("out.jsonl");
+
+        let mut sink = JsonLinesSink::create_compressed(
+            output.to_str().unwrap(),
+            OutputCompression::from_path(&output),
+        )
+        .unwrap();
+        sink.write_batch(&[]).unwrap();
+        assert!(!output.exists());
+        sink.finish().unwrap();
+
+        assert_eq!(fs::read_to_string(&output).unwrap(), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_file_keeps_permissions_of_replaced_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = tempdir().unwrap();
+        let target = tmp_dir.path().join("out.jsonl");
+        fs::write(&target, "previous\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let file = AtomicFile::create(&target).unwrap();
+        file.file().unwrap().write_all(b"new\n").unwrap();
+        file.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new\n");
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,303 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{Progress, Setup, event};
+    use etl_rust::extract::checkpoint::{Checkpoint, Manifest, ProgressLog};
+    use etl_rust::{Config, EtlError, OutputFormat, PartitionKey, run};
+    use flate2::read::MultiGzDecoder;
+    use std::fs::{self, OpenOptions};
+    use std::io::{Read, Write};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn write_hour(s: &Setup, hour: u32, ids: &[u32]) -> PathBuf {
+        s.write(&format!("2024-01-01-{}.json", hour), ids)
+    }
+
+    fn config(s: &Setup, resume: bool) -> Config {
+        Config {
+            checkpoint_file: Some(s.state.to_str().unwrap().to_string()),
+            resume,
+            ..s.config()
+        }
+    }
+
+    fn ids(output: &Path) -> Vec<String> {
+        let mut ids: Vec<String> = fs::read_to_string(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["id"].to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Two hourly files holding the events 1 to 3.
+    fn setup(output_name: &str) -> Setup {
+        let s = Setup::new(output_name);
+        write_hour(&s, 0, &[1, 2]);
+        write_hour(&s, 1, &[3]);
+        s
+    }
+
+    #[test]
+    fn checkpoint_records_processed_files() {
+        let s = setup("out.jsonl");
+
+        run(config(&s, false)).unwrap();
+
+        let manifest = Manifest::load(&s.state).unwrap().unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(
+            manifest.outputs.output_bytes,
+            Some(fs::metadata(&s.output).unwrap().len())
+        );
+        let record = manifest
+            .files
+            .iter()
+            .find(|(path, _)| path.ends_with("2024-01-01-0.json"))
+            .unwrap()
+            .1;
+        assert_eq!(
+            record.size,
+            fs::metadata(s.input.join("2024-01-01-0.json"))
+                .unwrap()
+                .len()
+        );
+        assert_eq!(record.sha256.len(), 64);
+    }
+
+    #[test]
+    fn resume_skips_processed_files_and_appends() {
+        let s = setup("out.jsonl");
+        run(config(&s, false)).unwrap();
+        write_hour(&s, 2, &[4]);
+
+        let summary = run(config(&s, true)).unwrap();
+
+        assert_eq!(summary.skipped_files, 2);
+        assert_eq!(summary.files, 1);
+        assert_eq!(ids(&s.output), ["\"1\"", "\"2\"", "\"3\"", "\"4\""]);
+    }
+
+    #[test]
+    fn resume_drops_output_written_after_last_checkpoint() {
+        let s = setup("out.jsonl");
+        run(config(&s, false)).unwrap();
+        // What a run killed between two checkpoints leaves behind.
+        let mut file = OpenOptions::new().append(true).open(&s.output).unwrap();
+        write!(file, "{}\n{{\"id\":\"9", event(9)).unwrap();
+        drop(file);
+        write_hour(&s, 2, &[4]);
+
+        run(config(&s, true)).unwrap();
+
+        assert_eq!(ids(&s.output), ["\"1\"", "\"2\"", "\"3\"", "\"4\""]);
+    }
+
+    #[test]
+    fn run_without_resume_starts_over() {
+        let s = setup("out.jsonl");
+        run(config(&s, false)).unwrap();
+
+        let summary = run(config(&s, false)).unwrap();
+
+        assert_eq!(summary.skipped_files, 0);
+        assert_eq!(ids(&s.output).len(), 3);
+    }
+
+    #[test]
+    fn resume_without_checkpoint_file_is_rejected() {
+        let s = setup("out.jsonl");
+        fs::write(&s.output, "previous\n").unwrap();
+
+        let err = run(config(&s, true)).unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+        assert!(err.to_string().contains("does not exist"));
+        assert_eq!(fs::read_to_string(&s.output).unwrap(), "previous\n");
+    }
+
+    #[test]
+    fn changed_file_is_processed_again() {
+        let s = setup("out.jsonl");
+        run(config(&s, false)).unwrap();
+        write_hour(&s, 1, &[3, 5]);
+
+        let summary = run(config(&s, true)).unwrap();
+
+        assert_eq!(summary.skipped_files, 1);
+        assert_eq!(summary.events, 2);
+    }
+
+    #[test]
+    fn touched_file_with_same_content_is_skipped() {
+        let s = setup("out.jsonl");
+        run(config(&s, false)).unwrap();
+        let file = fs::File::options()
+            .write(true)
+            .open(s.input.join("2024-01-01-1.json"))
+            .unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(3600))
+            .unwrap();
+
+        let summary = run(config(&s, true)).unwrap();
+
+        assert_eq!(summary.skipped_files, 2);
+    }
+
+    #[test]
+    fn file_changed_while_processed_is_not_recorded() {
+        let s = setup("out.jsonl");
+        let mut checkpoint =
+            Checkpoint::load(s.state.to_str().unwrap(), &config(&s, false)).unwrap();
+        let files = checkpoint.select(vec![
+            s.input.join("2024-01-01-0.json"),
+            s.input.join("2024-01-01-1.json"),
+        ]);
+        write_hour(&s, 1, &[3, 4]);
+
+        for path in &files {
+            checkpoint.file_done(path);
+        }
+        checkpoint.save().unwrap();
+
+        let manifest = Manifest::load(&s.state).unwrap().unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert!(
+            manifest
+                .files
+                .keys()
+                .all(|path| path.ends_with("2024-01-01-0.json"))
+        );
+    }
+
+    #[test]
+    fn streaming_run_is_checkpointed() {
+        let s = setup("out.jsonl");
+        run(Config {
+            streaming: true,
+            ..config(&s, false)
+        })
+        .unwrap();
+        write_hour(&s, 2, &[4]);
+
+        let summary = run(Config {
+            streaming: true,
+            ..config(&s, true)
+        })
+        .unwrap();
+
+        assert_eq!(summary.skipped_files, 2);
+        assert_eq!(ids(&s.output).len(), 4);
+    }
+
+    #[test]
+    fn aborted_streaming_run_resumes_without_duplicates() {
+        Setup::new("out.jsonl").abort_and_complete(Progress::Checkpoint);
+    }
+
+    #[test]
+    fn resumed_gzip_output_stays_readable() {
+        let s = setup("out.jsonl.gz");
+        run(config(&s, false)).unwrap();
+        write_hour(&s, 2, &[4]);
+
+        run(config(&s, true)).unwrap();
+
+        let mut content = String::new();
+        MultiGzDecoder::new(fs::File::open(&s.output).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content.lines().count(), 4);
+    }
+
+    #[test]
+    fn resumed_csv_output_has_one_header() {
+        let s = setup("out.csv");
+        let csv = |resume| Config {
+            output_format: OutputFormat::Csv,
+            columns: vec!["id".to_string()],
+            ..config(&s, resume)
+        };
+        run(csv(false)).unwrap();
+        write_hour(&s, 2, &[4]);
+
+        run(csv(true)).unwrap();
+
+        assert_eq!(fs::read_to_string(&s.output).unwrap(), "id\n1\n2\n3\n4\n");
+    }
+
+    #[test]
+    fn resumed_partitioned_output_keeps_earlier_parts() {
+        let s = setup("out");
+        let partitioned = |resume| Config {
+            partition_by: vec![PartitionKey::Type],
+            ..config(&s, resume)
+        };
+        let partition = s.output.join("type=PushEvent");
+        let parts = || {
+            let mut names: Vec<String> = fs::read_dir(&partition)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect();
+            names.sort();
+            names
+        };
+        run(partitioned(false)).unwrap();
+        let earlier = parts();
+        write_hour(&s, 2, &[4]);
+
+        run(partitioned(true)).unwrap();
+
+        // Every checkpoint commits the open parts; the resumed run adds one after them.
+        let all = parts();
+        assert_eq!(all.len(), earlier.len() + 1);
+        assert!(all.starts_with(&earlier));
+        let added = partition.join(all.last().unwrap());
+        assert_eq!(fs::read_to_string(added).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn checkpoint_for_another_output_is_rejected() {
+        let s = setup("out.jsonl");
+        run(config(&s, false)).unwrap();
+
+        let other = s.output.with_file_name("other.jsonl");
+        let err = run(Config {
+            output_file: Some(other.to_str().unwrap().to_string()),
+            ..config(&s, true)
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+        assert!(err.to_string().contains("was written for output"));
+        assert!(!other.exists());
+    }
+
+    #[test]
+    fn parquet_output_cannot_be_checkpointed() {
+        let s = setup("out.parquet");
+        let err = run(Config {
+            output_format: OutputFormat::Parquet,
+            ..config(&s, false)
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    #[test]
+    fn resume_requires_checkpoint_file() {
+        let s = setup("out.jsonl");
+        let err = run(Config {
+            checkpoint_file: None,
+            ..config(&s, true)
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+}
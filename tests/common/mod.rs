@@ -0,0 +1,123 @@
+//! Fixtures shared by the tests that run whole extractions. Each test file includes this
+//! module on its own and uses only part of it.
+#![allow(dead_code)]
+
+use etl_rust::{Config, run};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::{TempDir, tempdir};
+
+/// A minimal valid event line.
+pub fn event(id: u32) -> String {
+    format!(
+        r#"{{"id":"{id}","type":"PushEvent","actor":{{"id":1,"login":"a","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{}},"public":true,"created_at":"2024-01-01T00:00:00Z"}}"#
+    )
+}
+
+/// Where a run records the files it has done, to carry on from there next time.
+#[derive(Debug, Clone, Copy)]
+pub enum Progress {
+    Checkpoint,
+    Incremental,
+}
+
+/// A temporary directory with an empty `input` folder, and the paths of the output and of
+/// a progress file (checkpoint or incremental state) next to it.
+pub struct Setup {
+    pub tmp_dir: TempDir,
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub state: PathBuf,
+}
+
+impl Setup {
+    pub fn new(output_name: &str) -> Self {
+        let tmp_dir = tempdir().unwrap();
+        let input = tmp_dir.path().join("input");
+        fs::create_dir(&input).unwrap();
+        Setup {
+            output: tmp_dir.path().join(output_name),
+            state: tmp_dir.path().join("state.json"),
+            input,
+            tmp_dir,
+        }
+    }
+
+    /// A path in the temporary directory, outside of the input folder.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.tmp_dir.path().join(name)
+    }
+
+    /// Writes the events `ids` to the input file `name`.
+    pub fn write(&self, name: &str, ids: &[u32]) -> PathBuf {
+        let lines: Vec<String> = ids.iter().map(|id| event(*id)).collect();
+        self.write_lines(name, &lines)
+    }
+
+    pub fn write_lines(&self, name: &str, lines: &[String]) -> PathBuf {
+        let path = self.input.join(name);
+        fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    /// Reads the input folder into the output, quietly.
+    pub fn config(&self) -> Config {
+        Config {
+            path_to_data: self.input.to_str().unwrap().to_string(),
+            output_file: Some(self.output.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        }
+    }
+
+    /// Lines in the output, 0 when it was not written.
+    pub fn output_lines(&self) -> usize {
+        fs::read_to_string(&self.output)
+            .map(|content| content.lines().count())
+            .unwrap_or(0)
+    }
+
+    /// Streams three hourly files with progress recorded in `state`. A corrupt line at the
+    /// end of the last file aborts the first run; once it is fixed, a second run must
+    /// complete the output with every event written exactly once.
+    pub fn abort_and_complete(&self, progress: Progress) {
+        let many: Vec<u32> = (10_000..30_000).collect();
+        self.write("2015-01-01-15.json", &many);
+        self.write("2015-01-01-16.json", &[3000]);
+        let mut lines: Vec<String> = (40_000..50_000).map(event).collect();
+        lines.push("{not json".to_string());
+        self.write_lines("2015-01-01-17.json", &lines);
+        let state = Some(self.state.to_str().unwrap().to_string());
+        let streamed = |again| {
+            let config = Config {
+                streaming: true,
+                max_in_flight: 100,
+                max_errors: Some(0),
+                fail_fast: true,
+                ..self.config()
+            };
+            match progress {
+                Progress::Checkpoint => Config {
+                    checkpoint_file: state.clone(),
+                    resume: again,
+                    ..config
+                },
+                Progress::Incremental => Config {
+                    incremental_state: state.clone(),
+                    ..config
+                },
+            }
+        };
+
+        assert!(run(streamed(false)).unwrap().aborted);
+        lines.pop();
+        self.write_lines("2015-01-01-17.json", &lines);
+        run(streamed(true)).unwrap();
+
+        let content = fs::read_to_string(&self.output).unwrap();
+        let events: HashSet<&str> = content.lines().collect();
+        assert_eq!(events.len(), content.lines().count());
+        assert_eq!(events.len(), many.len() + 1 + lines.len());
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::filters::save_events;
+    use etl_rust::extract::json_lines::receive_all;
+    use etl_rust::{Compression, Config, EtlError, OutputFormat, PartitionKey, run};
+    use flate2::read::MultiGzDecoder;
+    use std::fs;
+    use std::io::Read;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn event(id: u32, event_type: &str) -> String {
+        format!(
+            r#"{{"id":"{id}","type":"{event_type}","actor":{{"id":1,"login":"a","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{}},"public":true,"created_at":"2024-01-01T00:00:00Z"}}"#
+        )
+    }
+
+    fn gunzip(path: &Path) -> String {
+        let mut content = String::new();
+        MultiGzDecoder::new(fs::File::open(path).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    fn input_dir(root: &Path) -> std::path::PathBuf {
+        let dir = root.join("input");
+        fs::create_dir(&dir).unwrap();
+        let content = [
+            event(1, "PushEvent"),
+            event(2, "PushEvent"),
+            event(3, "PushEvent"),
+            event(4, "WatchEvent"),
+        ]
+        .join("\n");
+        fs::write(dir.join("events.json"), content).unwrap();
+        dir
+    }
+
+    fn config(input: &Path, output: &Path) -> Config {
+        Config {
+            path_to_data: input.to_str().unwrap().to_string(),
+            output_file: Some(output.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gzip_chosen_by_extension() {
+        let tmp_dir = tempdir().unwrap();
+        let input = input_dir(tmp_dir.path());
+        let output = tmp_dir.path().join("out.json.gz");
+
+        run(config(&input, &output)).unwrap();
+        // A second run replaces the output instead of adding a gzip member.
+        run(config(&input, &output)).unwrap();
+
+        assert_eq!(gunzip(&output).lines().count(), 4);
+        assert_eq!(
+            receive_all(output.to_str().unwrap(), None).unwrap().len(),
+            4
+        );
+    }
+
+    #[test]
+    fn zstd_chosen_by_flag_with_level() {
+        let tmp_dir = tempdir().unwrap();
+        let input = input_dir(tmp_dir.path());
+        let output = tmp_dir.path().join("out.jsonl");
+
+        let config = Config {
+            compress: Some(Compression::Zstd),
+            compress_level: Some(19),
+            ..config(&input, &output)
+        };
+        run(config).unwrap();
+
+        let content = zstd::decode_all(fs::File::open(&output).unwrap()).unwrap();
+        assert_eq!(String::from_utf8(content).unwrap().lines().count(), 4);
+    }
+
+    #[test]
+    fn partitioned_rotated_parts_are_compressed() {
+        let tmp_dir = tempdir().unwrap();
+        let input = input_dir(tmp_dir.path());
+        let output = tmp_dir.path().join("out");
+
+        let config = Config {
+            partition_by: vec![PartitionKey::Type],
+            max_part_events: Some(2),
+            compress: Some(Compression::Gzip),
+            ..config(&input, &output)
+        };
+        run(config).unwrap();
+
+        let push = output.join("type=PushEvent");
+        assert_eq!(gunzip(&push.join("part-0000.jsonl.gz")).lines().count(), 2);
+        assert_eq!(gunzip(&push.join("part-0001.jsonl.gz")).lines().count(), 1);
+        let watch = output.join("type=WatchEvent/part-0000.jsonl.gz");
+        assert_eq!(gunzip(&watch).lines().count(), 1);
+    }
+
+    #[test]
+    fn csv_and_reject_files_are_compressed() {
+        let tmp_dir = tempdir().unwrap();
+        let input = input_dir(tmp_dir.path());
+        fs::write(input.join("broken.json"), "not json").unwrap();
+        let output = tmp_dir.path().join("out.csv.gz");
+        let rejects = tmp_dir.path().join("rejects.jsonl.gz");
+
+        let config = Config {
+            output_format: OutputFormat::Csv,
+            columns: vec!["id".to_string()],
+            reject_file: Some(rejects.to_str().unwrap().to_string()),
+            ..config(&input, &output)
+        };
+        run(config).unwrap();
+
+        let csv = gunzip(&output);
+        assert!(csv.starts_with("id\n"));
+        assert_eq!(csv.lines().count(), 5);
+        assert!(gunzip(&rejects).contains("not json"));
+    }
+
+    #[test]
+    fn save_events_appends_gzip_members() {
+        let tmp_dir = tempdir().unwrap();
+        let input = input_dir(tmp_dir.path());
+        let events = receive_all(input.join("events.json").to_str().unwrap(), None).unwrap();
+        let output = tmp_dir.path().join("saved.json.gz");
+
+        save_events(&events[..1], output.to_str().unwrap()).unwrap();
+        save_events(&events[1..], output.to_str().unwrap()).unwrap();
+
+        assert_eq!(gunzip(&output).lines().count(), 4);
+    }
+
+    #[test]
+    fn invalid_compression_options() {
+        let tmp_dir = tempdir().unwrap();
+        let input = input_dir(tmp_dir.path());
+
+        let config_with = |output: &str, compress, level, format| Config {
+            compress,
+            compress_level: level,
+            output_format: format,
+            ..config(&input, &tmp_dir.path().join(output))
+        };
+
+        let err = run(config_with(
+            "out.jsonl",
+            Some(Compression::Gzip),
+            Some(12),
+            OutputFormat::JsonLines,
+        ))
+        .unwrap_err();
+        assert!(matches!(err, EtlError::Config(_)));
+        assert!(err.to_string().contains("compression level 12"));
+
+        let err = run(config_with(
+            "out.jsonl",
+            None,
+            Some(3),
+            OutputFormat::JsonLines,
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("needs a compressed output"));
+
+        let err = run(config_with(
+            "out.db",
+            Some(Compression::Zstd),
+            None,
+            OutputFormat::Sqlite,
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot be compressed"));
+
+        assert!("lz4".parse::<Compression>().is_err());
+        assert_eq!("zst".parse::<Compression>().unwrap(), Compression::Zstd);
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::filters::save_events_as;
+    use etl_rust::extract::json_lines::receive_all;
+    use etl_rust::model::fields;
+    use etl_rust::{Config, EtlError, OutputFormat, run};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn comment_event() -> &'static str {
+        r#"{"id":"7","type":"IssueCommentEvent","actor":{"id":1,"login":"octocat","gravatar_id":"","url":"","avatar_url":""},"repo":{"id":1,"name":"owner/repo","url":""},"payload":{"action":"created","comment":{"body":"first line, with \"quotes\"\nsecond line"},"pull_request":{"head":{"ref":"feature"}}},"public":true,"created_at":"2024-01-01T12:30:00Z"}"#
+    }
+
+    fn read_records(path: &Path, delimiter: u8) -> Vec<Vec<String>> {
+        csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .from_path(path)
+            .unwrap()
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    fn run_delimited(
+        format: OutputFormat,
+        columns: &[&str],
+    ) -> Result<(tempfile::TempDir, std::path::PathBuf), EtlError> {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        fs::write(input_dir.join("events.json"), comment_event()).unwrap();
+
+        let output_file = tmp_dir.path().join("events.out");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            output_format: format,
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        run(config)?;
+        Ok((tmp_dir, output_file))
+    }
+
+    #[test]
+    fn csv_uses_default_columns_with_header() {
+        let (_tmp_dir, output_file) = run_delimited(OutputFormat::Csv, &[]).unwrap();
+        let records = read_records(&output_file, b',');
+        assert_eq!(
+            records,
+            [
+                vec!["id", "type", "actor.login", "repo.name", "created_at"],
+                vec![
+                    "7",
+                    "IssueCommentEvent",
+                    "octocat",
+                    "owner/repo",
+                    "2024-01-01T12:30:00Z"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_projects_payload_paths_and_quotes_bodies() {
+        let columns = [
+            "id",
+            "payload.action",
+            "payload.comment.body",
+            "payload.pull_request.head.ref",
+            "org.login",
+        ];
+        let (_tmp_dir, output_file) = run_delimited(OutputFormat::Csv, &columns).unwrap();
+
+        let raw = fs::read_to_string(&output_file).unwrap();
+        assert!(raw.contains(
+            r#""first line, with ""quotes""
+second line""#
+        ));
+
+        let records = read_records(&output_file, b',');
+        assert_eq!(records[0], columns);
+        assert_eq!(
+            records[1],
+            [
+                "7",
+                "created",
+                "first line, with \"quotes\"\nsecond line",
+                "feature",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn tsv_uses_tabs() {
+        let (_tmp_dir, output_file) =
+            run_delimited(OutputFormat::Tsv, &["id", "actor.id", "public"]).unwrap();
+        let raw = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(raw, "id\tactor.id\tpublic\n7\t1\ttrue\n");
+    }
+
+    #[test]
+    fn every_known_field_is_a_column() {
+        let columns: Vec<&str> = fields::known_fields().collect();
+        let (_tmp_dir, output_file) = run_delimited(OutputFormat::Csv, &columns).unwrap();
+
+        let records = read_records(&output_file, b',');
+        assert_eq!(records[0], columns);
+        assert_eq!(records[1].len(), columns.len());
+    }
+
+    #[test]
+    fn unknown_column_is_rejected() {
+        let err = run_delimited(OutputFormat::Csv, &["id", "actor.name"]).unwrap_err();
+        assert!(matches!(err, EtlError::Config(_)));
+        assert!(err.to_string().contains("Unknown column 'actor.name'"));
+    }
+
+    #[test]
+    fn columns_require_a_delimited_format() {
+        let err = run_delimited(OutputFormat::JsonLines, &["id"]).unwrap_err();
+        assert!(err.to_string().contains("--columns"));
+    }
+
+    #[test]
+    fn save_events_as_csv() {
+        let tmp_dir = tempdir().unwrap();
+        let input = tmp_dir.path().join("events.json");
+        fs::write(&input, comment_event()).unwrap();
+        let events = receive_all(input.to_str().unwrap(), None).unwrap();
+
+        let output_file = tmp_dir.path().join("events.csv");
+        let config = Config {
+            output_format: OutputFormat::Csv,
+            columns: vec!["repo.name".to_string()],
+            ..Default::default()
+        };
+        save_events_as(&events, output_file.to_str().unwrap(), &config).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output_file).unwrap(),
+            "repo.name\nowner/repo\n"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::discovery::{InputDiscovery, PathGlob, list_input_files, natural_cmp};
+    use etl_rust::{Config, EtlError, InputOrder, run};
+    use std::cmp::Ordering;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    fn names(folder: &Path, order: InputOrder) -> Vec<String> {
+        list_input_files(folder.to_str().unwrap(), None, None, order)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn write_files(folder: &Path, files: &[(&str, usize)]) {
+        for (name, size) in files {
+            fs::write(folder.join(name), "x".repeat(*size)).unwrap();
+        }
+    }
+
+    /// `gharchive/2024/01/01/2024-01-01-0.json` style layout under `root`.
+    fn write_archive(root: &Path, hours: &[&str]) {
+        for hour in hours {
+            let (date, _) = hour.rsplit_once('-').unwrap();
+            let dir = root.join(date.replace('-', "/"));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(format!("{}.json", hour)), "").unwrap();
+        }
+    }
+
+    fn discover(roots: &[&Path], configure: impl FnOnce(&mut InputDiscovery)) -> Vec<String> {
+        let mut discovery = InputDiscovery {
+            roots: roots.iter().map(|root| root.to_path_buf()).collect(),
+            recursive: true,
+            ..Default::default()
+        };
+        configure(&mut discovery);
+        let root = roots[0];
+        discovery
+            .list()
+            .unwrap()
+            .iter()
+            .map(|path| {
+                path.strip_prefix(root)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| path.clone())
+                    .display()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn time_order_is_chronological_across_days() {
+        let tmp_dir = tempdir().unwrap();
+        write_files(
+            tmp_dir.path(),
+            &[
+                ("2015-01-02-0.json", 1),
+                ("2015-01-01-23.json.gz", 1),
+                ("2015-01-01-9.json", 1),
+                ("2014-12-31-23.json", 1),
+                ("2015-01-01-10.json", 1),
+            ],
+        );
+
+        assert_eq!(
+            names(tmp_dir.path(), InputOrder::Time),
+            [
+                "2014-12-31-23.json",
+                "2015-01-01-9.json",
+                "2015-01-01-10.json",
+                "2015-01-01-23.json.gz",
+                "2015-01-02-0.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn time_order_puts_other_files_last_in_natural_order() {
+        let tmp_dir = tempdir().unwrap();
+        write_files(
+            tmp_dir.path(),
+            &[
+                ("events-10.json", 1),
+                ("2015-01-01-0.json", 1),
+                ("events-2.json", 1),
+            ],
+        );
+
+        assert_eq!(
+            names(tmp_dir.path(), InputOrder::Time),
+            ["2015-01-01-0.json", "events-2.json", "events-10.json"]
+        );
+    }
+
+    #[test]
+    fn name_order_is_natural() {
+        let tmp_dir = tempdir().unwrap();
+        write_files(
+            tmp_dir.path(),
+            &[("b.json", 1), ("a-10.json", 1), ("a-9.json", 1)],
+        );
+
+        assert_eq!(
+            names(tmp_dir.path(), InputOrder::Name),
+            ["a-9.json", "a-10.json", "b.json"]
+        );
+    }
+
+    #[test]
+    fn size_order_is_smallest_first() {
+        let tmp_dir = tempdir().unwrap();
+        write_files(
+            tmp_dir.path(),
+            &[("large.json", 30), ("small.json", 10), ("medium.json", 20)],
+        );
+
+        assert_eq!(
+            names(tmp_dir.path(), InputOrder::Size),
+            ["small.json", "medium.json", "large.json"]
+        );
+    }
+
+    #[test]
+    fn natural_cmp_compares_numbers_by_value() {
+        assert_eq!(natural_cmp("file-9", "file-10"), Ordering::Less);
+        assert_eq!(natural_cmp("file-10", "file-9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "b"), Ordering::Less);
+        assert_eq!(natural_cmp("file", "file-1"), Ordering::Less);
+        assert_eq!(natural_cmp("file-01", "file-1"), Ordering::Less);
+        assert_eq!(natural_cmp("file-1", "file-1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn input_order_parses_names() {
+        assert_eq!("TIME".parse::<InputOrder>().unwrap(), InputOrder::Time);
+        assert_eq!("none".parse::<InputOrder>().unwrap(), InputOrder::None);
+        assert_eq!(InputOrder::Size.to_string(), "size");
+        let err = "random".parse::<InputOrder>().unwrap_err();
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    #[test]
+    fn output_follows_input_order() {
+        let tmp_dir = tempdir().unwrap();
+        let input = tmp_dir.path().join("input");
+        fs::create_dir(&input).unwrap();
+        for (name, id) in [
+            ("2015-01-02-0.json", 3),
+            ("2015-01-01-23.json", 2),
+            ("2015-01-01-9.json", 1),
+        ] {
+            fs::write(
+                input.join(name),
+                format!(
+                    r#"{{"id":"{id}","type":"PushEvent","actor":{{"id":1,"login":"a","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{}},"public":true,"created_at":"2024-01-01T00:00:00Z"}}"#
+                ),
+            )
+            .unwrap();
+        }
+        let output = tmp_dir.path().join("out.jsonl");
+
+        run(Config {
+            path_to_data: input.to_str().unwrap().to_string(),
+            output_file: Some(output.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let ids: Vec<String> = fs::read_to_string(&output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["id"].to_string())
+            .collect();
+        assert_eq!(ids, ["\"1\"", "\"2\"", "\"3\""]);
+    }
+
+    #[test]
+    fn recursive_listing_walks_nested_folders_in_order() {
+        let tmp_dir = tempdir().unwrap();
+        write_archive(
+            tmp_dir.path(),
+            &[
+                "2024-01-02-0",
+                "2024-01-01-10",
+                "2024-01-01-9",
+                "2023-12-31-23",
+            ],
+        );
+
+        assert_eq!(
+            discover(&[tmp_dir.path()], |_| {}),
+            [
+                "2023/12/31/2023-12-31-23.json",
+                "2024/01/01/2024-01-01-9.json",
+                "2024/01/01/2024-01-01-10.json",
+                "2024/01/02/2024-01-02-0.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn listing_is_not_recursive_by_default() {
+        let tmp_dir = tempdir().unwrap();
+        write_archive(tmp_dir.path(), &["2024-01-01-0"]);
+        fs::write(tmp_dir.path().join("top.json"), "").unwrap();
+
+        assert_eq!(names(tmp_dir.path(), InputOrder::Time), ["top.json"]);
+    }
+
+    #[test]
+    fn several_roots_are_merged_and_files_listed_once() {
+        let tmp_dir = tempdir().unwrap();
+        let (a, b) = (tmp_dir.path().join("a"), tmp_dir.path().join("b"));
+        write_archive(&a, &["2024-01-01-1"]);
+        write_archive(&b, &["2024-01-01-0"]);
+        let single = tmp_dir.path().join("2024-01-01-2.json");
+        fs::write(&single, "").unwrap();
+
+        let files = discover(&[tmp_dir.path(), &a, &b, &single], |_| {});
+
+        assert_eq!(
+            files,
+            [
+                "b/2024/01/01/2024-01-01-0.json",
+                "a/2024/01/01/2024-01-01-1.json",
+                "2024-01-01-2.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn include_and_exclude_globs() {
+        let tmp_dir = tempdir().unwrap();
+        write_archive(
+            tmp_dir.path(),
+            &[
+                "2023-12-31-23",
+                "2024-01-01-0",
+                "2024-01-01-1",
+                "2024-02-01-0",
+            ],
+        );
+
+        let files = discover(&[tmp_dir.path()], |discovery| {
+            discovery.include = vec![PathGlob::parse("2024/**").unwrap()];
+            discovery.exclude = vec![
+                PathGlob::parse("*-1.json").unwrap(),
+                PathGlob::parse("2024/02/**").unwrap(),
+            ];
+        });
+
+        assert_eq!(files, ["2024/01/01/2024-01-01-0.json"]);
+    }
+
+    #[test]
+    fn name_glob_does_not_cross_folders() {
+        let glob = PathGlob::parse("2024-*.json").unwrap();
+        assert!(glob.matches(Path::new("2024/01/01/2024-01-01-0.json")));
+
+        let glob = PathGlob::parse("2024/*.json").unwrap();
+        assert!(!glob.matches(Path::new("2024/01/01/2024-01-01-0.json")));
+        assert!(glob.matches(Path::new("2024/2024-01-01-0.json")));
+    }
+
+    #[test]
+    fn invalid_glob_is_rejected() {
+        let err = PathGlob::parse("[2024").unwrap_err();
+        assert!(matches!(err, EtlError::InvalidFilter { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loops_are_skipped() {
+        let tmp_dir = tempdir().unwrap();
+        write_archive(tmp_dir.path(), &["2024-01-01-0"]);
+        std::os::unix::fs::symlink(tmp_dir.path(), tmp_dir.path().join("2024/loop")).unwrap();
+
+        assert_eq!(
+            discover(&[tmp_dir.path()], |_| {}),
+            ["2024/01/01/2024-01-01-0.json"]
+        );
+    }
+
+    #[test]
+    fn missing_root_is_an_error() {
+        let tmp_dir = tempdir().unwrap();
+        let missing = tmp_dir.path().join("missing");
+        let err = InputDiscovery {
+            roots: vec![tmp_dir.path().to_path_buf(), missing],
+            ..Default::default()
+        }
+        .list()
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Io { .. }));
+    }
+
+    #[test]
+    fn run_reads_nested_archive_from_config() {
+        let tmp_dir = tempdir().unwrap();
+        write_archive(tmp_dir.path(), &["2024-01-01-0", "2024-01-01-1"]);
+        for file in [
+            "2024/01/01/2024-01-01-0.json",
+            "2024/01/01/2024-01-01-1.json",
+        ] {
+            fs::write(
+                tmp_dir.path().join(file),
+                r#"{"id":"1","type":"PushEvent","actor":{"id":1,"login":"a","gravatar_id":"","url":"","avatar_url":""},"repo":{"id":1,"name":"owner/repo","url":""},"payload":{},"public":true,"created_at":"2024-01-01T00:00:00Z"}"#,
+            )
+            .unwrap();
+        }
+
+        let summary = run(Config {
+            path_to_data: tmp_dir.path().join("2024").display().to_string(),
+            recursive: true,
+            exclude: vec!["*-1.json".to_string()],
+            quiet_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.events, 1);
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::filters::{EventFilter, NamePattern, parse_time_bound};
+    use etl_rust::extract::json_lines::receive_all;
+    use etl_rust::{Config, EXIT_DATA_ERROR, EXIT_FATAL, EXIT_IO_ERROR, EtlError};
+    use std::error::Error;
+    use std::path::Path;
+
+    #[test]
+    fn io_error_keeps_path_and_source() {
+        let err = receive_all("/non/existent/file.json", None).unwrap_err();
+        match &err {
+            EtlError::Io { path, source, .. } => {
+                assert_eq!(path, Path::new("/non/existent/file.json"));
+                assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+            }
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+        assert!(err.to_string().contains("/non/existent/file.json"));
+        assert!(err.source().is_some());
+        assert_eq!(err.exit_code(), EXIT_IO_ERROR);
+    }
+
+    #[test]
+    fn parse_error_reports_position_in_file() {
+        let source = serde_json::from_str::<serde_json::Value>(r#"{"id": }"#).unwrap_err();
+        let err = EtlError::parse("2024-01-01-0.json", 12, source);
+
+        assert!(matches!(
+            err,
+            EtlError::Parse {
+                line: 12,
+                column: 8,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "2024-01-01-0.json:12:8: expected value");
+        assert!(err.source().unwrap().is::<serde_json::Error>());
+        assert_eq!(err.exit_code(), EXIT_DATA_ERROR);
+    }
+
+    #[test]
+    fn invalid_where_expression_chains_the_expression_error() {
+        let config = Config {
+            where_clause: Some("repo.nam == \"x\"".to_string()),
+            ..Default::default()
+        };
+        let err = EventFilter::from_config(&config).unwrap_err();
+
+        assert!(matches!(err, EtlError::InvalidFilter { .. }));
+        assert!(err.to_string().starts_with("Invalid --where expression"));
+        let source = err.source().unwrap();
+        assert!(source.to_string().contains("unknown field 'repo.nam'"));
+        assert_eq!(err.exit_code(), EXIT_FATAL);
+    }
+
+    #[test]
+    fn invalid_patterns_and_times_are_filter_errors() {
+        let err = NamePattern::parse("re:(unclosed").unwrap_err();
+        assert!(matches!(
+            err,
+            EtlError::InvalidFilter {
+                source: Some(_),
+                ..
+            }
+        ));
+
+        let err = NamePattern::parse("").unwrap_err();
+        assert!(matches!(err, EtlError::InvalidFilter { source: None, .. }));
+
+        let err = parse_time_bound("yesterday", chrono::Utc::now()).unwrap_err();
+        assert!(err.to_string().contains("Invalid timestamp 'yesterday'"));
+        assert!(err.source().is_some());
+    }
+}
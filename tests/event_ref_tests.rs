@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use etl_rust::EtlError;
+    use etl_rust::extract::event_ref::{for_each_event_ref, parse_event_ref};
+    use std::borrow::Cow;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn event(id: u32, title: &str) -> String {
+        format!(
+            r#"{{"id":"{id}","type":"IssuesEvent","actor":{{"id":1,"login":"octocat","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":2,"name":"owner/repo","url":""}},"payload":{{"action":"opened","issue":{{"title":"{title}"}}}},"public":true,"created_at":"2024-01-01T10:00:00Z","org":{{"id":3,"login":"owner","gravatar_id":"","url":"","avatar_url":""}}}}"#
+        )
+    }
+
+    #[test]
+    fn parses_borrowed_fields() {
+        let mut line = event(7, "title").into_bytes();
+
+        let parsed = parse_event_ref(&mut line).unwrap();
+
+        assert_eq!(parsed.id, "7");
+        assert_eq!(parsed.event_type, "IssuesEvent");
+        assert_eq!(parsed.actor.login, "octocat");
+        assert_eq!(parsed.repo.name, "owner/repo");
+        assert_eq!(parsed.org.unwrap().login, "owner");
+        assert_eq!(parsed.payload.action.as_deref(), Some("opened"));
+        assert_eq!(
+            parsed.created_at,
+            Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap()
+        );
+        assert!(matches!(parsed.actor.login, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn unescapes_strings() {
+        let mut line = event(1, "ignored")
+            .replace(r#""login":"octocat""#, r#""login":"octo\"cat""#)
+            .into_bytes();
+
+        let parsed = parse_event_ref(&mut line).unwrap();
+
+        assert_eq!(parsed.actor.login, "octo\"cat");
+    }
+
+    #[test]
+    fn missing_field_is_an_error() {
+        let mut line = br#"{"id":"1","type":"PushEvent"}"#.to_vec();
+
+        assert!(parse_event_ref(&mut line).is_err());
+    }
+
+    #[test]
+    fn reads_file_skipping_blank_lines_and_passing_parse_errors() {
+        let tmp_dir = tempdir().unwrap();
+        let path = tmp_dir.path().join("2024-01-01-10.json");
+        fs::write(
+            &path,
+            format!("{}\n\nnot json\r\n{}\r\n", event(1, "a"), event(2, "b")),
+        )
+        .unwrap();
+
+        let mut ids = Vec::new();
+        let mut errors = Vec::new();
+        let lines = for_each_event_ref(&path, |event| {
+            match event {
+                Ok(event) => ids.push(event.id.into_owned()),
+                Err(err) => errors.push(err),
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(ids, ["1", "2"]);
+        assert_eq!(lines, 3);
+        assert!(matches!(errors[..], [EtlError::Parse { line: 3, .. }]));
+    }
+
+    #[test]
+    fn handler_error_stops_reading() {
+        let tmp_dir = tempdir().unwrap();
+        let path = tmp_dir.path().join("events.json");
+        fs::write(&path, format!("{}\n{}", event(1, "a"), event(2, "b"))).unwrap();
+
+        let mut seen = 0;
+        let err = for_each_event_ref(&path, |_| {
+            seen += 1;
+            Err(EtlError::Config("stop".to_string()))
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let tmp_dir = tempdir().unwrap();
+        let err = for_each_event_ref(&tmp_dir.path().join("missing.json"), |_| Ok(())).unwrap_err();
+
+        assert!(matches!(err, EtlError::Io { .. }));
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::expr::Predicate;
+    use etl_rust::model::fields::{self, FieldType, FieldValue};
+    use etl_rust::model::github::GitHubEvent;
+    use serde_json::json;
+
+    fn issue_event(repo: &str, action: &str) -> GitHubEvent {
+        let json = format!(
+            r#"{{"id":"42","type":"IssuesEvent","actor":{{"id":9,"login":"octocat","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"{}","url":""}},"payload":{{"action":"{}","issue":{{"number":17,"title":"Crash","user":{{"login":"reporter"}},"labels":[{{"name":"bug"}}]}}}},"public":true,"created_at":"2024-03-01T10:00:00Z"}}"#,
+            repo, action
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn matches(source: &str, event: &GitHubEvent) -> bool {
+        Predicate::parse(source).unwrap().matches(event)
+    }
+
+    #[test]
+    fn test_where_combines_type_action_and_regex() {
+        let source =
+            r#"type == "IssuesEvent" && payload.action == "opened" && repo.name ~ "^apache/""#;
+        assert!(matches(source, &issue_event("apache/spark", "opened")));
+        assert!(!matches(source, &issue_event("apache/spark", "closed")));
+        assert!(!matches(source, &issue_event("rust-lang/rust", "opened")));
+    }
+
+    #[test]
+    fn test_where_precedence_and_negation() {
+        let event = issue_event("apache/spark", "opened");
+        assert!(matches(
+            r#"type == "PushEvent" || type == "IssuesEvent" && public"#,
+            &event
+        ));
+        assert!(!matches(r#"!(actor.login == "octocat")"#, &event));
+        assert!(matches(r#"repo.name !~ "^rust-lang/""#, &event));
+    }
+
+    #[test]
+    fn test_where_numbers_timestamps_and_nested_payload() {
+        let event = issue_event("apache/spark", "opened");
+        assert!(matches(
+            "payload.issue.number >= 17 && actor.id < 10",
+            &event
+        ));
+        assert!(matches(r#"created_at >= "2024-03-01T00:00:00Z""#, &event));
+        assert!(!matches(r#"created_at < "2024-03-01T10:00:00Z""#, &event));
+        assert!(matches(
+            r#"payload.issue.user.login == "reporter" && payload.issue.labels.0.name == "bug""#,
+            &event
+        ));
+    }
+
+    #[test]
+    fn test_where_payload_lists() {
+        let event = issue_event("apache/spark", "opened");
+        assert!(matches(
+            r#"payload.issue.labels == "[{\"name\":\"bug\"}]""#,
+            &event
+        ));
+        assert!(matches("payload.issue.labels.1.name == null", &event));
+        assert!(matches("payload.issue.assignees == null", &event));
+    }
+
+    #[test]
+    fn test_where_null_for_missing_values() {
+        let event = issue_event("apache/spark", "opened");
+        assert!(matches("org.login == null", &event));
+        assert!(matches("payload.release.tag_name == null", &event));
+        assert!(!matches("payload.action == null", &event));
+    }
+
+    #[test]
+    fn test_where_unknown_field_points_at_column() {
+        let source = r#"type == "IssuesEvent" && repo.nam ~ "^apache/""#;
+        let err = Predicate::parse(source).unwrap_err();
+        assert_eq!(err.column, 26);
+        assert!(err.message.contains("unknown field 'repo.nam'"));
+        assert!(err.message.contains("repo.name"));
+
+        let rendered = err.render(source);
+        assert!(rendered.ends_with(&format!("\n  {}^", " ".repeat(25))));
+    }
+
+    #[test]
+    fn test_where_type_errors() {
+        let err = Predicate::parse(r#"actor.id == "9""#).unwrap_err();
+        assert_eq!(err.column, 10);
+        assert!(err.message.contains("cannot compare number with string"));
+
+        let err = Predicate::parse("repo.name").unwrap_err();
+        assert_eq!(err.column, 1);
+        assert!(err.message.contains("expected a boolean condition"));
+
+        let err = Predicate::parse(r#"actor.id ~ "1""#).unwrap_err();
+        assert!(err.message.contains("needs a string on the left"));
+
+        let err = Predicate::parse(r#"created_at > "yesterday""#).unwrap_err();
+        assert_eq!(err.column, 14);
+        assert!(err.message.contains("invalid RFC 3339 timestamp"));
+
+        let err = Predicate::parse(r#"repo.name ~ "(""#).unwrap_err();
+        assert!(err.message.contains("invalid regex"));
+    }
+
+    #[test]
+    fn test_where_syntax_errors() {
+        let err = Predicate::parse(r#"type = "PushEvent""#).unwrap_err();
+        assert_eq!(err.column, 6);
+
+        let err = Predicate::parse(r#"type == "PushEvent"#).unwrap_err();
+        assert_eq!(err.column, 9);
+        assert!(err.message.contains("unterminated string"));
+
+        let err = Predicate::parse(r#"(public && type == "PushEvent""#).unwrap_err();
+        assert!(err.message.contains("expected ')'"));
+
+        let err = Predicate::parse(r#"public public"#).unwrap_err();
+        assert_eq!(err.column, 8);
+
+        let err = Predicate::parse("public &&").unwrap_err();
+        assert!(err.message.contains("unexpected end"));
+    }
+
+    #[test]
+    fn test_where_nesting_limit() {
+        let nested = |depth| format!("{}public{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Predicate::parse(&nested(256)).is_ok());
+        let err = Predicate::parse(&nested(257)).unwrap_err();
+        assert_eq!(err.column, 257);
+        assert!(err.message.contains("nested more than 256 levels"));
+
+        let err = Predicate::parse(&format!("{}public", "!".repeat(100_000))).unwrap_err();
+        assert!(err.message.contains("nested more than 256 levels"));
+    }
+
+    #[test]
+    fn test_where_long_chains() {
+        let event = issue_event("apache/spark", "opened");
+        let and_chain = vec!["public"; 100_000].join(" && ");
+        assert!(matches(&and_chain, &event));
+        let or_chain = format!("{} || public", vec!["!public"; 100_000].join(" || "));
+        assert!(matches(&or_chain, &event));
+        assert!(!matches(&format!("{} && !public", and_chain), &event));
+    }
+
+    #[test]
+    fn test_field_values_match_their_types() {
+        // An event with every known field set to a value of its type.
+        let mut root = json!({});
+        for name in fields::known_fields() {
+            let value = match fields::field_type(name).unwrap() {
+                FieldType::String => json!("PushEvent"),
+                FieldType::Number => json!(7),
+                FieldType::Bool => json!(true),
+                FieldType::Timestamp => json!("2024-03-01T10:00:00Z"),
+                FieldType::Any => json!([{ "login": "octocat" }]),
+            };
+            let (parents, last) = name.rsplit_once('.').unwrap_or(("", name));
+            let mut parent = &mut root;
+            for segment in parents.split('.').filter(|segment| !segment.is_empty()) {
+                parent = parent
+                    .as_object_mut()
+                    .unwrap()
+                    .entry(segment)
+                    .or_insert_with(|| json!({}));
+            }
+            parent[last] = value;
+        }
+        let event: GitHubEvent = serde_json::from_value(root).unwrap();
+
+        for name in fields::known_fields() {
+            let field_type = fields::field_type(name).unwrap();
+            let value = fields::lookup(&event, name);
+            let matches = matches!(
+                (field_type, &value),
+                (FieldType::String | FieldType::Any, FieldValue::Str(_))
+                    | (FieldType::Number, FieldValue::Number(_))
+                    | (FieldType::Bool, FieldValue::Bool(_))
+                    | (FieldType::Timestamp, FieldValue::Timestamp(_))
+            );
+            assert!(
+                matches,
+                "{} is a {} field, read {:?}",
+                name,
+                field_type.name(),
+                value
+            );
+        }
+        assert_eq!(
+            fields::lookup(&event, "payload.issue.user.0.login"),
+            FieldValue::Str("octocat".into())
+        );
+    }
+}
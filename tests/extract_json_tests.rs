@@ -21,7 +21,12 @@ mod tests {
     fn test_check_folder_with_invalid_path() {
         let result = check_folder("/non/existent/path", false, false, None, None, false);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Unable to read folder"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Unable to read folder")
+        );
     }
 
     #[test]
@@ -180,7 +185,12 @@ mod tests {
             false,
         );
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Invalid event type"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid event type")
+        );
     }
 
     #[test]
@@ -462,6 +472,118 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
+
+    #[test]
+    fn test_receive_all_with_gzip_file() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let tmp_dir = tempdir().unwrap();
+        let file_path = tmp_dir.path().join("2015-01-01-15.json.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        write!(encoder, "{}\n{}\n", valid_event(), pr_event()).unwrap();
+        fs::write(&file_path, encoder.finish().unwrap()).unwrap();
+
+        let result = receive_all(file_path.to_str().unwrap(), None);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_receive_all_with_zstd_file() {
+        let tmp_dir = tempdir().unwrap();
+        let file_path = tmp_dir.path().join("events.json.zst");
+        let content = format!("{}\n{}", valid_event(), create_event());
+        fs::write(
+            &file_path,
+            zstd::stream::encode_all(content.as_bytes(), 0).unwrap(),
+        )
+        .unwrap();
+
+        let result = receive_all(file_path.to_str().unwrap(), None);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_receive_all_with_bzip2_file() {
+        use bzip2::{Compression, write::BzEncoder};
+        use std::io::Write;
+
+        let tmp_dir = tempdir().unwrap();
+        let file_path = tmp_dir.path().join("events.json.bz2");
+        let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+        write!(encoder, "{}", valid_event()).unwrap();
+        fs::write(&file_path, encoder.finish().unwrap()).unwrap();
+
+        let result = receive_all(file_path.to_str().unwrap(), None);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_receive_all_with_corrupt_gzip_file() {
+        let tmp_dir = tempdir().unwrap();
+        let file_path = tmp_dir.path().join("broken.json.gz");
+        fs::write(&file_path, valid_event()).unwrap();
+
+        let result = receive_all(file_path.to_str().unwrap(), None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_archive_hour_from_file_name() {
+        use chrono::{TimeZone, Utc};
+        use etl_rust::extract::discovery::archive_hour;
+        use std::path::Path;
+
+        assert_eq!(
+            archive_hour(Path::new("/data/2015-01-01-15.json.gz")),
+            Some(Utc.with_ymd_and_hms(2015, 1, 1, 15, 0, 0).unwrap())
+        );
+        assert_eq!(
+            archive_hour(Path::new("2015-01-02-0.json")),
+            Some(Utc.with_ymd_and_hms(2015, 1, 2, 0, 0, 0).unwrap())
+        );
+        assert_eq!(archive_hour(Path::new("events.json")), None);
+        assert_eq!(archive_hour(Path::new("2015-13-01-0.json")), None);
+    }
+
+    #[test]
+    fn test_list_input_files_skips_hours_outside_window() {
+        use chrono::{TimeZone, Utc};
+        use etl_rust::InputOrder;
+        use etl_rust::extract::discovery::list_input_files;
+
+        let tmp_dir = tempdir().unwrap();
+        for name in [
+            "2015-01-01-14.json",
+            "2015-01-01-15.json.gz",
+            "2015-01-01-16.json",
+            "2015-01-01-17.json",
+            "other.json",
+        ] {
+            fs::write(tmp_dir.path().join(name), "").unwrap();
+        }
+
+        let files = list_input_files(
+            tmp_dir.path().to_str().unwrap(),
+            Some(Utc.with_ymd_and_hms(2015, 1, 1, 15, 30, 0).unwrap()),
+            Some(Utc.with_ymd_and_hms(2015, 1, 1, 17, 0, 0).unwrap()),
+            InputOrder::Time,
+        )
+        .unwrap();
+
+        let mut names: Vec<String> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["2015-01-01-15.json.gz", "2015-01-01-16.json", "other.json"]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -727,4 +849,38 @@ mod save_tests {
         let lines: Vec<&str> = content.lines().filter(|l| !l.is_empty()).collect();
         assert_eq!(lines.len(), 5);
     }
+
+    #[test]
+    fn test_save_events_from_compressed_files() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        write!(encoder, "{}\n{}", valid_event(), pr_event()).unwrap();
+        fs::write(
+            input_dir.join("2015-01-01-15.json.gz"),
+            encoder.finish().unwrap(),
+        )
+        .unwrap();
+        fs::write(input_dir.join("2015-01-01-16.json"), create_event()).unwrap();
+        fs::write(input_dir.join("notes.txt.gz"), "ignored").unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let result = check_folder(
+            input_dir.to_str().unwrap(),
+            false,
+            false,
+            None,
+            Some(output_file.to_str().unwrap().to_string()),
+            true,
+        );
+
+        assert!(result.is_ok());
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 3);
+    }
 }
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use etl_rust::EtlError;
+    use etl_rust::extract::analysis::count_events;
+    use etl_rust::extract::filters::is_event_type_name;
+    use etl_rust::extract::filters::{EventFilter, NamePattern, parse_time_bound, should_include};
+    use etl_rust::model::github::{EventType, GitHubEvent};
+
+    fn event(repo: &str, org: Option<&str>) -> GitHubEvent {
+        let org = org
+            .map(|login| {
+                format!(
+                    r#","org":{{"id":7,"login":"{}","gravatar_id":"","url":"","avatar_url":""}}"#,
+                    login
+                )
+            })
+            .unwrap_or_default();
+        let json = format!(
+            r#"{{"id":"1","type":"PushEvent","actor":{{"id":1,"login":"user","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"{}","url":""}},"payload":{{}},"public":true,"created_at":"2024-01-01T00:00:00Z"{}}}"#,
+            repo, org
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn repo_filter(patterns: &[&str]) -> EventFilter {
+        EventFilter {
+            repos: patterns
+                .iter()
+                .map(|p| NamePattern::parse(p).unwrap())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_exact_pattern_ignores_case() {
+        let pattern = NamePattern::parse("Rust-Lang/Rust").unwrap();
+        assert!(pattern.matches("rust-lang/rust"));
+        assert!(!pattern.matches("rust-lang/rustup"));
+    }
+
+    #[test]
+    fn test_glob_pattern_stays_within_owner() {
+        let pattern = NamePattern::parse("rust-lang/*").unwrap();
+        assert!(pattern.matches("rust-lang/cargo"));
+        assert!(pattern.matches("RUST-LANG/cargo"));
+        assert!(!pattern.matches("rust-lang-nursery/cargo"));
+        assert!(!pattern.matches("rust-lang"));
+    }
+
+    #[test]
+    fn test_regex_pattern() {
+        let pattern = NamePattern::parse("re:^apache/(spark|flink)$").unwrap();
+        assert!(pattern.matches("apache/spark"));
+        assert!(!pattern.matches("apache/kafka"));
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_rejected() {
+        assert!(NamePattern::parse("re:(unclosed").is_err());
+        assert!(NamePattern::parse("rust-lang/[").is_err());
+        assert!(NamePattern::parse("").is_err());
+    }
+
+    #[test]
+    fn test_repo_filter_matches_any_pattern() {
+        let filter = repo_filter(&["rust-lang/*", "tokio-rs/tokio"]);
+        assert!(should_include(&event("rust-lang/rust", None), &filter));
+        assert!(should_include(&event("tokio-rs/tokio", None), &filter));
+        assert!(!should_include(&event("tokio-rs/mio", None), &filter));
+    }
+
+    #[test]
+    fn test_org_filter_requires_org() {
+        let filter = EventFilter {
+            orgs: vec![NamePattern::parse("kubernetes").unwrap()],
+            ..Default::default()
+        };
+        assert!(should_include(
+            &event("kubernetes/kubernetes", Some("kubernetes")),
+            &filter
+        ));
+        assert!(!should_include(
+            &event("kubernetes/kubernetes", None),
+            &filter
+        ));
+        assert!(!should_include(&event("helm/helm", Some("helm")), &filter));
+    }
+
+    #[test]
+    fn test_empty_filter_includes_everything() {
+        let filter = EventFilter::default();
+        assert!(should_include(&event("any/repo", None), &filter));
+    }
+
+    #[test]
+    fn test_parse_time_bound_rfc3339() {
+        let now = Utc::now();
+        let parsed = parse_time_bound("2024-01-01T15:30:00+02:00", now).unwrap();
+        assert_eq!(parsed, Utc.with_ymd_and_hms(2024, 1, 1, 13, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn test_parse_time_bound_relative() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+        assert_eq!(
+            parse_time_bound("-6h", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 2, 6, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time_bound("-1d", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time_bound("-30m", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 2, 11, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_time_bound_rejects_garbage() {
+        let now = Utc::now();
+        assert!(parse_time_bound("yesterday", now).is_err());
+        assert!(parse_time_bound("-6x", now).is_err());
+        assert!(parse_time_bound("-", now).is_err());
+        assert!(parse_time_bound("-h", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_time_bound_rejects_non_ascii_unit() {
+        let now = Utc::now();
+        for value in ["-6é", "-ä", "-1ｈ"] {
+            let err = parse_time_bound(value, now).unwrap_err();
+            assert!(matches!(err, EtlError::InvalidFilter { .. }));
+        }
+    }
+
+    #[test]
+    fn test_time_window_is_half_open() {
+        let filter = EventFilter {
+            since: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            until: Some(Utc.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        let mut inside = event("a/b", None);
+        assert!(should_include(&inside, &filter));
+
+        inside.created_at = Utc.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap();
+        assert!(!should_include(&inside, &filter));
+
+        inside.created_at = Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 59).unwrap();
+        assert!(!should_include(&inside, &filter));
+    }
+
+    #[test]
+    fn test_unknown_event_type_round_trips() {
+        let json = serde_json::to_string(&event("a/b", None))
+            .unwrap()
+            .replace("PushEvent", "SponsorshipEvent");
+        let parsed: GitHubEvent = serde_json::from_str(&json).unwrap();
+        assert!(matches!(&parsed.event_type, EventType::Other(name) if name == "SponsorshipEvent"));
+        assert_eq!(parsed.event_type.as_str(), "SponsorshipEvent");
+
+        let serialized = serde_json::to_string(&parsed).unwrap();
+        assert!(serialized.contains(r#""type":"SponsorshipEvent""#));
+    }
+
+    #[test]
+    fn test_known_event_type_is_not_other() {
+        let parsed = event("a/b", None);
+        assert!(matches!(parsed.event_type, EventType::PushEvent));
+    }
+
+    #[test]
+    fn test_count_events_includes_unknown_types() {
+        let mut sponsorship = event("a/b", None);
+        sponsorship.event_type = EventType::Other("SponsorshipEvent".to_string());
+        let counts = count_events(&[event("a/b", None), sponsorship.clone(), sponsorship]);
+        assert_eq!(counts.get("PushEvent"), Some(&1));
+        assert_eq!(counts.get("SponsorshipEvent"), Some(&2));
+    }
+
+    #[test]
+    fn test_filter_on_unknown_event_type() {
+        let mut sponsorship = event("a/b", None);
+        sponsorship.event_type = EventType::Other("SponsorshipEvent".to_string());
+        let filter = EventFilter {
+            event_types: vec!["SponsorshipEvent".to_string()],
+            ..Default::default()
+        };
+        assert!(should_include(&sponsorship, &filter));
+        assert!(!should_include(&event("a/b", None), &filter));
+    }
+
+    #[test]
+    fn test_event_type_name_shape() {
+        assert!(is_event_type_name("SponsorshipEvent"));
+        assert!(!is_event_type_name("sponsorshipEvent"));
+        assert!(!is_event_type_name("Event"));
+        assert!(!is_event_type_name("InvalidEventType"));
+        assert!(!is_event_type_name("Push-Event"));
+    }
+}
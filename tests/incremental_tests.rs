@@ -0,0 +1,177 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{Progress, Setup, event};
+    use chrono::{TimeZone, Utc};
+    use etl_rust::extract::incremental::IncrementalState;
+    use etl_rust::{Config, EtlError, PartitionKey, run};
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::path::Path;
+
+    fn config(s: &Setup) -> Config {
+        Config {
+            incremental_state: Some(s.state.to_str().unwrap().to_string()),
+            ..s.config()
+        }
+    }
+
+    fn load_state(s: &Setup) -> IncrementalState {
+        IncrementalState::load(&s.state).unwrap().unwrap()
+    }
+
+    #[test]
+    fn first_run_processes_everything_and_records_the_mark() {
+        let s = Setup::new("out.jsonl");
+        s.write("2015-01-01-9.json", &[1]);
+        s.write("2015-01-01-15.json", &[2, 3]);
+
+        let summary = run(config(&s)).unwrap();
+
+        assert_eq!(summary.events, 3);
+        assert_eq!(
+            load_state(&s).high_water_mark,
+            Some(Utc.with_ymd_and_hms(2015, 1, 1, 15, 0, 0).unwrap())
+        );
+        assert!(load_state(&s).retry.is_empty());
+    }
+
+    #[test]
+    fn next_run_appends_only_new_hours() {
+        let s = Setup::new("out.jsonl");
+        s.write("2015-01-01-15.json", &[1, 2]);
+        run(config(&s)).unwrap();
+        s.write("2015-01-01-16.json", &[3]);
+        s.write("2015-01-02-0.json", &[4]);
+
+        let summary = run(config(&s)).unwrap();
+
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.skipped_files, 1);
+        assert_eq!(s.output_lines(), 4);
+        assert_eq!(
+            load_state(&s).high_water_mark,
+            Some(Utc.with_ymd_and_hms(2015, 1, 2, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn run_without_new_files_leaves_output_as_is() {
+        let s = Setup::new("out.jsonl");
+        s.write("2015-01-01-15.json", &[1, 2]);
+        run(config(&s)).unwrap();
+        let before = fs::read_to_string(&s.output).unwrap();
+
+        let summary = run(config(&s)).unwrap();
+
+        assert_eq!(summary.files, 0);
+        assert_eq!(fs::read_to_string(&s.output).unwrap(), before);
+    }
+
+    #[test]
+    fn first_run_appends_to_existing_output() {
+        let s = Setup::new("out.jsonl");
+        s.write("2015-01-01-15.json", &[1]);
+        fs::write(&s.output, format!("{}\n", event(0))).unwrap();
+
+        run(config(&s)).unwrap();
+
+        assert_eq!(s.output_lines(), 2);
+    }
+
+    #[test]
+    fn files_without_hour_are_not_selected() {
+        let s = Setup::new("out.jsonl");
+        s.write("2015-01-01-15.json", &[1]);
+        s.write("events.json", &[2]);
+
+        let summary = run(config(&s)).unwrap();
+
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.skipped_files, 1);
+    }
+
+    #[test]
+    fn failed_file_is_retried_without_repeating_later_hours() {
+        let s = Setup::new("out.jsonl");
+        fs::write(s.input.join("2015-01-01-15.json.gz"), "not gzip data").unwrap();
+        s.write("2015-01-01-16.json", &[2]);
+
+        let summary = run(config(&s)).unwrap();
+        assert_eq!(summary.failed_files, 1);
+        let state = load_state(&s);
+        assert_eq!(
+            state.high_water_mark,
+            Some(Utc.with_ymd_and_hms(2015, 1, 1, 16, 0, 0).unwrap())
+        );
+        assert_eq!(state.retry.len(), 1);
+        assert!(state.retry[0].ends_with("2015-01-01-15.json.gz"));
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        write!(encoder, "{}", event(1)).unwrap();
+        fs::write(
+            s.input.join("2015-01-01-15.json.gz"),
+            encoder.finish().unwrap(),
+        )
+        .unwrap();
+
+        let summary = run(config(&s)).unwrap();
+        assert_eq!(summary.files, 1);
+        assert_eq!(s.output_lines(), 2);
+        assert!(load_state(&s).retry.is_empty());
+    }
+
+    #[test]
+    fn leftovers_of_a_crashed_run_are_dropped() {
+        let s = Setup::new("out.jsonl");
+        s.write("2015-01-01-15.json", &[1]);
+        run(config(&s)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&s.output).unwrap();
+        write!(file, "{{\"id\":\"partial").unwrap();
+        drop(file);
+        s.write("2015-01-01-16.json", &[2]);
+
+        run(config(&s)).unwrap();
+
+        let content = fs::read_to_string(&s.output).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("partial"));
+    }
+
+    #[test]
+    fn partitioned_output_gets_new_parts() {
+        let s = Setup::new("out.jsonl");
+        let config = || Config {
+            partition_by: vec![PartitionKey::Type],
+            ..config(&s)
+        };
+        s.write("2015-01-01-15.json", &[1]);
+        run(config()).unwrap();
+        s.write("2015-01-01-16.json", &[2]);
+
+        run(config()).unwrap();
+
+        let partition = Path::new(&s.output).join("type=PushEvent");
+        assert!(partition.join("part-0000.jsonl").exists());
+        assert!(partition.join("part-0001.jsonl").exists());
+    }
+
+    #[test]
+    fn aborted_streaming_run_is_completed_without_duplicates() {
+        Setup::new("out.jsonl").abort_and_complete(Progress::Incremental);
+    }
+
+    #[test]
+    fn incremental_and_checkpoint_are_exclusive() {
+        let s = Setup::new("out.jsonl");
+        let err = run(Config {
+            checkpoint_file: Some(s.state.with_extension("ckpt").display().to_string()),
+            ..config(&s)
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+}
@@ -1,4 +1,4 @@
-use etl_rust::{Config, run};
+use etl_rust::{Config, EXIT_DATA_ERROR, EXIT_IO_ERROR, EtlError, run};
 
 #[cfg(test)]
 mod tests {
@@ -11,13 +11,16 @@ mod tests {
         let config = Config {
             path_to_data: "/non/existent/path".into(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         let err = run(config).unwrap_err();
-        assert!(err.contains("Unable to read folder"));
+        assert!(err.to_string().contains("Unable to read folder"));
+        assert!(matches!(err, EtlError::Io { .. }));
+        assert_eq!(err.exit_code(), EXIT_IO_ERROR);
     }
 
     #[test]
@@ -26,10 +29,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -46,10 +50,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -66,10 +71,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -86,10 +92,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -102,10 +109,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -122,10 +130,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -142,10 +151,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: true,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -160,15 +170,16 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: true,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: Some(output_file.to_str().unwrap().to_string()),
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
 
         run(config).unwrap();
 
-        assert!(output_file.exists());
+        assert!(!output_file.exists());
     }
 
     #[test]
@@ -180,10 +191,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: Some("PushEvent".to_string()),
+            event_type_filter: vec!["PushEvent".to_string()],
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -197,16 +209,19 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: Some("InvalidEventType".to_string()),
+            event_type_filter: vec!["InvalidEventType".to_string()],
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         let err = run(config).unwrap_err();
-        assert!(err.contains("Invalid event type"));
+        assert!(err.to_string().contains("Invalid event type"));
+        assert!(matches!(err, EtlError::InvalidFilter { .. }));
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn run_with_multiple_event_types_filters() {
         let tmp_dir = tempdir().unwrap();
         let events = vec![
@@ -222,10 +237,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: Some("PushEvent".to_string()),
+            event_type_filter: vec!["PushEvent".to_string()],
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -240,10 +256,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: Some(output_file.to_str().unwrap().to_string()),
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
 
         assert!(run(config).is_ok());
@@ -260,10 +277,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: Some(output_file.to_string()),
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
 
         let result = run(config);
@@ -271,6 +289,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn run_with_show_stats() {
         let tmp_dir = tempdir().unwrap();
         let events = vec![
@@ -284,10 +303,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: true,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -300,10 +320,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: true,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -317,10 +338,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: true,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -334,15 +356,17 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: true,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: true,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn run_with_all_options_combined() {
         let tmp_dir = tempdir().unwrap();
         let events = vec![
@@ -357,10 +381,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: Some("PushEvent".to_string()),
+            event_type_filter: vec!["PushEvent".to_string()],
             output_file: Some(output_file.to_str().unwrap().to_string()),
             show_stats: true,
             quiet_mode: false,
+            ..Default::default()
         };
 
         assert!(run(config).is_ok());
@@ -380,10 +405,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -400,10 +426,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -419,10 +446,11 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
     }
@@ -440,11 +468,675 @@ mod tests {
         let config = Config {
             path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
             dry_run: false,
-            event_type_filter: None,
+            event_type_filter: Vec::new(),
             output_file: None,
             show_stats: false,
             quiet_mode: false,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+    }
+
+    fn full_event(id: u32, event_type: &str, login: &str) -> String {
+        format!(
+            r#"{{"id":"{id}","type":"{event_type}","actor":{{"id":1,"login":"{login}","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{}},"public":true,"created_at":"2024-01-01T00:00:00Z"}}"#
+        )
+    }
+
+    #[test]
+    fn run_streaming_writes_every_event() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+
+        for file in 0..4 {
+            let lines: Vec<String> = (0..250)
+                .map(|i| full_event(file * 1000 + i, "PushEvent", "user"))
+                .collect();
+            fs::write(input_dir.join(format!("{}.json", file)), lines.join("\n")).unwrap();
+        }
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            show_stats: true,
+            quiet_mode: true,
+            streaming: true,
+            max_in_flight: 16,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 1000);
+    }
+
+    #[test]
+    fn run_streaming_applies_event_type_filter() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "user"),
+            full_event(2, "WatchEvent", "user"),
+            full_event(3, "PushEvent", "user"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            event_type_filter: vec!["PushEvent".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            streaming: true,
+            max_in_flight: 1,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("WatchEvent"));
+    }
+
+    #[test]
+    fn run_streaming_writes_nothing_from_a_failed_file() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        for i in 0..5000 {
+            writeln!(encoder, "{}", full_event(i, "PushEvent", "user")).unwrap();
+        }
+        let compressed = encoder.finish().unwrap();
+        // Fails to decompress partway through, after many valid lines.
+        fs::write(
+            input_dir.join("truncated.json.gz"),
+            &compressed[..compressed.len() / 2],
+        )
+        .unwrap();
+        fs::write(
+            input_dir.join("valid.json"),
+            full_event(9999, "WatchEvent", "user"),
+        )
+        .unwrap();
+
+        for streaming in [false, true] {
+            let output_file = tmp_dir.path().join(format!("output-{}.jsonl", streaming));
+            let config = Config {
+                path_to_data: input_dir.to_str().unwrap().to_string(),
+                output_file: Some(output_file.to_str().unwrap().to_string()),
+                quiet_mode: true,
+                streaming,
+                max_in_flight: 16,
+                ..Default::default()
+            };
+            assert_eq!(run(config).unwrap().failed_files, 1);
+
+            let content = fs::read_to_string(&output_file).unwrap();
+            assert_eq!(content.lines().count(), 1, "streaming: {}", streaming);
+            assert!(content.contains("WatchEvent"));
+        }
+    }
+
+    #[test]
+    fn run_streaming_rejects_zero_in_flight_limit() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            streaming: true,
+            max_in_flight: 0,
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(err.to_string().contains("max_in_flight"));
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    #[test]
+    fn run_with_actor_filter_is_case_insensitive() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "Octocat"),
+            full_event(2, "PushEvent", "someone"),
+            full_event(3, "WatchEvent", "octocat"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            actor_filter: vec!["OCTOCAT".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("someone"));
+    }
+
+    #[test]
+    fn run_with_multiple_actors_and_event_type() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "alice"),
+            full_event(2, "PushEvent", "bob"),
+            full_event(3, "WatchEvent", "alice"),
+            full_event(4, "PushEvent", "carol"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            event_type_filter: vec!["PushEvent".to_string()],
+            actor_filter: vec!["alice".to_string(), "bob".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("carol"));
+        assert!(!content.contains("WatchEvent"));
+    }
+
+    #[test]
+    fn run_with_repo_glob_filter() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "alice").replace("owner/repo", "rust-lang/rust"),
+            full_event(2, "PushEvent", "bob").replace("owner/repo", "rust-lang/cargo"),
+            full_event(3, "PushEvent", "carol"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            repo_filter: vec!["rust-lang/*".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("carol"));
+    }
+
+    #[test]
+    fn run_with_invalid_org_pattern() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            org_filter: vec!["re:(".to_string()],
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(err.to_string().contains("Invalid regex pattern"));
+    }
+
+    #[test]
+    fn run_with_since_and_until() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "early"),
+            full_event(2, "PushEvent", "inside")
+                .replace("2024-01-01T00:00:00Z", "2024-01-01T12:00:00Z"),
+            full_event(3, "PushEvent", "late")
+                .replace("2024-01-01T00:00:00Z", "2024-01-02T00:00:00Z"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            since: Some("2024-01-01T06:00:00Z".to_string()),
+            until: Some("2024-01-02T00:00:00Z".to_string()),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains("inside"));
+    }
+
+    #[test]
+    fn run_with_invalid_since() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            since: Some("last tuesday".to_string()),
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(err.to_string().contains("Invalid timestamp"));
+    }
+
+    #[test]
+    fn run_with_event_type_set_and_exclusion() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "user"),
+            full_event(2, "PullRequestEvent", "user"),
+            full_event(3, "WatchEvent", "user"),
+            full_event(4, "IssuesEvent", "user"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            event_type_filter: vec!["PushEvent".to_string(), "PullRequestEvent".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            exclude_event_type_filter: vec!["WatchEvent".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 3);
+        assert!(!content.contains("WatchEvent"));
+    }
+
+    #[test]
+    fn run_with_invalid_excluded_event_type() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            exclude_event_type_filter: vec!["WatchEvnt".to_string()],
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(err.to_string().contains("Invalid event type: 'WatchEvnt'"));
+    }
+
+    #[test]
+    fn run_with_where_expression() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "alice").replace("owner/repo", "apache/spark"),
+            full_event(2, "PushEvent", "bob").replace("owner/repo", "rust-lang/rust"),
+            full_event(3, "WatchEvent", "carol").replace("owner/repo", "apache/kafka"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            where_clause: Some(r#"type == "PushEvent" && repo.name ~ "^apache/""#.to_string()),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
         };
         assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains("alice"));
+    }
+
+    #[test]
+    fn run_with_invalid_where_expression() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            where_clause: Some("repo.nme == \"x\"".to_string()),
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Invalid --where expression at column 1")
+        );
+    }
+
+    #[test]
+    fn run_keeps_events_of_unknown_types() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "SponsorshipEvent", "alice"),
+            full_event(2, "PushEvent", "bob"),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            event_type_filter: vec!["SponsorshipEvent".to_string()],
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            show_stats: true,
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains(r#""type":"SponsorshipEvent""#));
+    }
+
+    fn run_with_reject_file(streaming: bool) {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            full_event(1, "PushEvent", "alice"),
+            r#"{"id": "broken""#.to_string(),
+            full_event(2, "PushEvent", "bob"),
+            r#"{"type":"PushEvent"}"#.to_string(),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("output.jsonl");
+        let reject_file = tmp_dir.path().join("rejected.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            reject_file: Some(reject_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            streaming,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 2);
+
+        let rejected: Vec<serde_json::Value> = fs::read_to_string(&reject_file)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(rejected[0]["line"], 2);
+        assert_eq!(rejected[0]["raw"], r#"{"id": "broken""#);
+        assert!(
+            rejected[0]["source"]
+                .as_str()
+                .unwrap()
+                .ends_with("events.json")
+        );
+        assert!(rejected[0]["error"].as_str().unwrap().contains("EOF"));
+        assert_eq!(rejected[1]["line"], 4);
+        assert!(
+            rejected[1]["error"]
+                .as_str()
+                .unwrap()
+                .contains("missing field")
+        );
+    }
+
+    #[test]
+    fn run_writes_rejected_lines() {
+        run_with_reject_file(false);
+    }
+
+    #[test]
+    fn run_streaming_writes_rejected_lines() {
+        run_with_reject_file(true);
+    }
+
+    #[test]
+    fn run_creates_empty_reject_file_without_rejects() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        fs::write(
+            input_dir.join("events.json"),
+            full_event(1, "PushEvent", "a"),
+        )
+        .unwrap();
+
+        let reject_file = tmp_dir.path().join("rejected.jsonl");
+        fs::write(&reject_file, "stale").unwrap();
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            reject_file: Some(reject_file.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert!(run(config).is_ok());
+        assert_eq!(fs::read_to_string(&reject_file).unwrap(), "");
+    }
+
+    /// One valid event followed by `bad` unparseable lines.
+    fn write_mostly_broken(dir: &std::path::Path, name: &str, bad: usize) {
+        let mut content = full_event(1, "PushEvent", "a");
+        for _ in 0..bad {
+            content.push_str("\n{\"id\": ");
+        }
+        fs::write(dir.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn run_clean_input_exits_zero() {
+        let tmp_dir = tempdir().unwrap();
+        fs::write(
+            tmp_dir.path().join("events.json"),
+            full_event(1, "PushEvent", "a"),
+        )
+        .unwrap();
+
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            max_errors: Some(0),
+            max_error_rate: Some(0.0),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let summary = run(config).unwrap();
+        assert_eq!(summary.lines, 1);
+        assert_eq!(summary.events, 1);
+        assert!(!summary.error_budget_exceeded);
+        assert_eq!(summary.exit_code(), 0);
+    }
+
+    #[test]
+    fn run_without_budget_ignores_rejected_lines() {
+        let tmp_dir = tempdir().unwrap();
+        write_mostly_broken(tmp_dir.path(), "events.json", 3);
+
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let summary = run(config).unwrap();
+        assert_eq!(summary.rejected, 3);
+        assert_eq!(summary.exit_code(), 0);
+    }
+
+    fn run_over_max_errors(streaming: bool) {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        write_mostly_broken(&input_dir, "events.json", 3);
+
+        let output_file = tmp_dir.path().join("out.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            max_errors: Some(2),
+            streaming,
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let summary = run(config).unwrap();
+        assert!(summary.error_budget_exceeded);
+        assert!(!summary.aborted);
+        assert_eq!(summary.exit_code(), EXIT_DATA_ERROR);
+        // Without --fail-fast the run still finishes and writes what it could parse.
+        assert_eq!(summary.lines, 4);
+        let content = fs::read_to_string(&output_file).unwrap();
+        assert_eq!(content.lines().count(), 1);
+    }
+
+    #[test]
+    fn run_over_max_errors_exits_with_data_error() {
+        run_over_max_errors(false);
+    }
+
+    #[test]
+    fn run_streaming_over_max_errors_exits_with_data_error() {
+        run_over_max_errors(true);
+    }
+
+    #[test]
+    fn run_within_max_errors_exits_zero() {
+        let tmp_dir = tempdir().unwrap();
+        write_mostly_broken(tmp_dir.path(), "events.json", 2);
+
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            max_errors: Some(2),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert_eq!(run(config).unwrap().exit_code(), 0);
+    }
+
+    #[test]
+    fn run_over_max_error_rate() {
+        let tmp_dir = tempdir().unwrap();
+        let lines: Vec<String> = (0..99)
+            .map(|id| full_event(id, "PushEvent", "a"))
+            .chain(["not json".to_string()])
+            .collect();
+        fs::write(tmp_dir.path().join("events.json"), lines.join("\n")).unwrap();
+
+        let config_with_rate = |rate: f64| Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            max_error_rate: Some(rate),
+            quiet_mode: true,
+            ..Default::default()
+        };
+
+        let summary = run(config_with_rate(0.01)).unwrap();
+        assert!(!summary.error_budget_exceeded);
+
+        let summary = run(config_with_rate(0.005)).unwrap();
+        assert!(summary.error_budget_exceeded);
+        assert_eq!(summary.exit_code(), EXIT_DATA_ERROR);
+    }
+
+    #[test]
+    fn run_with_invalid_max_error_rate() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            max_error_rate: Some(1.5),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(err.to_string().contains("max_error_rate"));
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    fn run_fail_fast(streaming: bool) {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        write_mostly_broken(&input_dir, "events.json", 5);
+
+        let output_file = tmp_dir.path().join("out.jsonl");
+        let reject_file = tmp_dir.path().join("rejected.jsonl");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            reject_file: Some(reject_file.to_str().unwrap().to_string()),
+            max_errors: Some(1),
+            fail_fast: true,
+            streaming,
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let summary = run(config).unwrap();
+        assert!(summary.aborted);
+        assert_eq!(summary.exit_code(), EXIT_DATA_ERROR);
+        // Reading stops at the first line over the limit.
+        assert_eq!(summary.rejected, 2);
+        let rejected = fs::read_to_string(&reject_file).unwrap();
+        assert_eq!(rejected.lines().count(), 2);
+    }
+
+    #[test]
+    fn run_fail_fast_stops_at_budget() {
+        run_fail_fast(false);
+    }
+
+    #[test]
+    fn run_streaming_fail_fast_stops_at_budget() {
+        run_fail_fast(true);
+    }
+
+    #[test]
+    fn run_with_unreadable_file_exits_with_io_error() {
+        let tmp_dir = tempdir().unwrap();
+        fs::write(
+            tmp_dir.path().join("good.json"),
+            full_event(1, "PushEvent", "a"),
+        )
+        .unwrap();
+        fs::write(tmp_dir.path().join("corrupt.json.gz"), "not gzip data").unwrap();
+
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let summary = run(config).unwrap();
+        assert_eq!(summary.failed_files, 1);
+        assert_eq!(summary.events, 1);
+        assert_eq!(summary.exit_code(), EXIT_IO_ERROR);
     }
 }
@@ -1,2 +1,21 @@
+// Every test file also builds as its own crate and includes the shared fixtures itself.
+#![allow(clippy::duplicate_mod)]
+
+pub mod atomic_output_tests;
+pub mod checkpoint_tests;
+pub mod compressed_output_tests;
+pub mod delimited_tests;
+pub mod discovery_tests;
+pub mod error_tests;
+pub mod event_ref_tests;
+pub mod expr_tests;
 pub mod extract_json_tests;
+pub mod filters_tests;
+pub mod incremental_tests;
 pub mod integration_tests;
+pub mod parallel_parse_tests;
+pub mod parquet_tests;
+pub mod partition_tests;
+pub mod source_tests;
+pub mod sqlite_tests;
+pub mod watch_tests;
@@ -0,0 +1,113 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{Setup, event};
+    use etl_rust::extract::filters::EventFilter;
+    use etl_rust::extract::json_lines::{ParsedLine, for_each_line};
+    use etl_rust::{Config, EtlError, run};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    /// Spans several parse batches.
+    const LINES: u32 = 10_000;
+
+    /// Every 1000th line is invalid and every 777th line blank.
+    fn write_large_file(path: &Path) {
+        let lines: Vec<String> = (1..=LINES)
+            .map(|line| match line {
+                line if line % 1000 == 0 => format!("invalid {}", line),
+                line if line % 777 == 0 => String::new(),
+                line => event(line),
+            })
+            .collect();
+        fs::write(path, lines.join("\n")).unwrap();
+    }
+
+    #[test]
+    fn large_file_keeps_line_order_and_numbers() {
+        let tmp_dir = tempdir().unwrap();
+        let path = tmp_dir.path().join("2024-01-01-0.json");
+        write_large_file(&path);
+
+        let mut events = Vec::new();
+        let mut rejected = Vec::new();
+        let lines = for_each_line(&path, &EventFilter::default(), |parsed| {
+            match parsed {
+                ParsedLine::Event(event) => events.push(event.id.parse::<u32>().unwrap()),
+                ParsedLine::Rejected(line) => {
+                    assert_eq!(line.raw, format!("invalid {}", line.line));
+                    rejected.push(line.line);
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let expected: Vec<u32> = (1..=LINES)
+            .filter(|line| line % 1000 != 0 && line % 777 != 0)
+            .collect();
+        assert_eq!(events, expected);
+        assert_eq!(rejected, (1..=10).map(|n| n * 1000).collect::<Vec<_>>());
+        assert_eq!(lines, expected.len() + rejected.len());
+    }
+
+    #[test]
+    fn handler_error_stops_reading() {
+        let tmp_dir = tempdir().unwrap();
+        let path = tmp_dir.path().join("2024-01-01-0.json");
+        write_large_file(&path);
+
+        let mut seen = 0;
+        let err = for_each_line(&path, &EventFilter::default(), |parsed| {
+            seen += 1;
+            match parsed {
+                ParsedLine::Event(_) => Ok(()),
+                ParsedLine::Rejected(line) => Err(EtlError::Config(format!("line {}", line.line))),
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "line 1000");
+        // 998 events, line 777 being blank, and the rejected line.
+        assert_eq!(seen, 999);
+    }
+
+    #[test]
+    fn output_of_large_file_is_in_input_order() {
+        let s = Setup::new("out.jsonl");
+        write_large_file(&s.input.join("2024-01-01-0.json"));
+        let reject_file = s.path("rejected.jsonl");
+
+        let summary = run(Config {
+            reject_file: Some(reject_file.to_str().unwrap().to_string()),
+            ..s.config()
+        })
+        .unwrap();
+
+        assert_eq!(summary.rejected, 10);
+        let ids: Vec<u32> = fs::read_to_string(&s.output)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["id"]
+                    .as_str()
+                    .unwrap()
+                    .parse()
+                    .unwrap()
+            })
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        let first_reject: serde_json::Value = serde_json::from_str(
+            fs::read_to_string(&reject_file)
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(first_reject["line"], 1000);
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(all(test, feature = "parquet"))]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt64Type;
+    use etl_rust::extract::parquet::ParquetCompression;
+    use etl_rust::{Config, EtlError, OutputFormat, run};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::basic::Compression;
+    use std::fs::{self, File};
+    use tempfile::tempdir;
+
+    fn event(id: u32, org: bool) -> String {
+        let org = if org {
+            r#","org":{"id":7,"login":"acme","gravatar_id":"","url":"","avatar_url":""}"#
+        } else {
+            ""
+        };
+        format!(
+            r#"{{"id":"{id}","type":"PushEvent","actor":{{"id":{id},"login":"user{id}","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{"ref":"refs/heads/main","push_id":99,"head":"abc123","repository_id":5}},"public":true,"created_at":"2024-01-01T00:00:00Z"{org}}}"#
+        )
+    }
+
+    #[test]
+    fn parses_compression_names() {
+        assert_eq!(
+            "snappy".parse::<ParquetCompression>().unwrap(),
+            ParquetCompression::Snappy
+        );
+        assert_eq!(
+            "ZSTD".parse::<ParquetCompression>().unwrap(),
+            ParquetCompression::Zstd(3)
+        );
+        assert_eq!(
+            "gzip:9".parse::<ParquetCompression>().unwrap(),
+            ParquetCompression::Gzip(9)
+        );
+        assert!("zstd:99".parse::<ParquetCompression>().is_err());
+        assert!("snappy:1".parse::<ParquetCompression>().is_err());
+        assert!("lzo".parse::<ParquetCompression>().is_err());
+    }
+
+    #[test]
+    fn parses_output_formats() {
+        assert_eq!(
+            "parquet".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Parquet
+        );
+        assert_eq!(
+            "jsonl".parse::<OutputFormat>().unwrap(),
+            OutputFormat::JsonLines
+        );
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    fn run_parquet(streaming: bool) {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [event(1, true), event(2, false), event(3, false)].join("\n");
+        fs::write(input_dir.join("events.json"), content).unwrap();
+
+        let output_file = tmp_dir.path().join("events.parquet");
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_file.to_str().unwrap().to_string()),
+            output_format: OutputFormat::Parquet,
+            row_group_size: 2,
+            parquet_compression: "zstd:5".to_string(),
+            streaming,
+            quiet_mode: true,
+            ..Default::default()
+        };
+        assert_eq!(run(config).unwrap().events, 3);
+
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&output_file).unwrap()).unwrap();
+        let metadata = builder.metadata().clone();
+        assert_eq!(metadata.file_metadata().num_rows(), 3);
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert!(matches!(
+            metadata.row_group(0).column(0).compression(),
+            Compression::ZSTD(_)
+        ));
+
+        let mut ids = Vec::new();
+        let mut missing_orgs = 0;
+        for batch in builder.build().unwrap() {
+            let batch = batch.unwrap();
+            let id_column = batch.column_by_name("id").unwrap().as_string::<i32>();
+            ids.extend(id_column.iter().flatten().map(str::to_string));
+            missing_orgs += batch.column_by_name("org_id").unwrap().null_count();
+
+            let push_ids = batch
+                .column_by_name("payload_push_id")
+                .unwrap()
+                .as_primitive::<UInt64Type>();
+            assert!(push_ids.iter().all(|id| id == Some(99)));
+            let text = |name| batch.column_by_name(name).unwrap().as_string::<i32>();
+            assert!(
+                text("payload_ref")
+                    .iter()
+                    .all(|r| r == Some("refs/heads/main"))
+            );
+            assert!(
+                text("payload_head")
+                    .iter()
+                    .all(|head| head == Some("abc123"))
+            );
+            assert!(text("payload_action").iter().all(|action| action.is_none()));
+            let repository_ids = batch
+                .column_by_name("payload_repository_id")
+                .unwrap()
+                .as_primitive::<UInt64Type>();
+            assert!(repository_ids.iter().all(|id| id == Some(5)));
+            let payload = batch.column_by_name("payload").unwrap().as_string::<i32>();
+            assert!(payload.value(0).contains("refs/heads/main"));
+        }
+        ids.sort();
+        assert_eq!(ids, ["1", "2", "3"]);
+        assert_eq!(missing_orgs, 2);
+    }
+
+    #[test]
+    fn run_writes_parquet_output() {
+        run_parquet(false);
+    }
+
+    #[test]
+    fn run_streaming_writes_parquet_output() {
+        run_parquet(true);
+    }
+
+    #[test]
+    fn run_with_invalid_parquet_compression() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            path_to_data: tmp_dir.path().to_str().unwrap().to_string(),
+            output_file: Some(
+                tmp_dir
+                    .path()
+                    .join("out.parquet")
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+            ),
+            output_format: OutputFormat::Parquet,
+            parquet_compression: "brotli".to_string(),
+            quiet_mode: true,
+            ..Default::default()
+        };
+        let err = run(config).unwrap_err();
+        assert!(matches!(err, EtlError::Config(_)));
+        assert!(err.to_string().contains("Invalid Parquet compression"));
+    }
+}
@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::partition::MAX_OPEN_PARTS;
+    use etl_rust::{Config, EtlError, OutputFormat, PartitionKey, run};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    fn event(id: u32, event_type: &str, created_at: &str, org: Option<&str>) -> String {
+        let org = org
+            .map(|login| {
+                format!(
+                    r#","org":{{"id":7,"login":"{login}","gravatar_id":"","url":"","avatar_url":""}}"#
+                )
+            })
+            .unwrap_or_default();
+        format!(
+            r#"{{"id":"{id}","type":"{event_type}","actor":{{"id":1,"login":"a","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"rust-lang/rust","url":""}},"payload":{{}},"public":true,"created_at":"{created_at}"{org}}}"#
+        )
+    }
+
+    fn config(input_dir: &Path, output_dir: &Path, keys: &[PartitionKey]) -> Config {
+        Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(output_dir.to_str().unwrap().to_string()),
+            partition_by: keys.to_vec(),
+            quiet_mode: true,
+            ..Default::default()
+        }
+    }
+
+    /// Part files below `root` as paths relative to it, with their number of lines.
+    fn parts(root: &Path) -> Vec<(String, usize)> {
+        fn walk(dir: &Path, found: &mut Vec<PathBuf>) {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    walk(&path, found);
+                } else {
+                    found.push(path);
+                }
+            }
+        }
+        let mut found = Vec::new();
+        walk(root, &mut found);
+        let mut parts: Vec<_> = found
+            .into_iter()
+            .map(|path| {
+                let lines = fs::read_to_string(&path).unwrap().lines().count();
+                let relative = path.strip_prefix(root).unwrap();
+                (relative.to_str().unwrap().replace('\\', "/"), lines)
+            })
+            .collect();
+        parts.sort();
+        parts
+    }
+
+    fn write_input(dir: &Path) {
+        let content = [
+            event(1, "PushEvent", "2024-01-01T15:10:00Z", Some("rust-lang")),
+            event(2, "PushEvent", "2024-01-01T15:20:00Z", None),
+            event(3, "PushEvent", "2024-01-01T16:00:00Z", None),
+            event(4, "WatchEvent", "2024-01-02T03:00:00Z", Some("my org/x")),
+        ]
+        .join("\n");
+        fs::write(dir.join("events.json"), content).unwrap();
+    }
+
+    #[test]
+    fn partitions_by_type_date_and_hour() {
+        let tmp_dir = tempdir().unwrap();
+        write_input(tmp_dir.path());
+        let output_dir = tmp_dir.path().join("out");
+
+        let keys = [PartitionKey::Type, PartitionKey::Date, PartitionKey::Hour];
+        run(config(tmp_dir.path(), &output_dir, &keys)).unwrap();
+
+        assert_eq!(
+            parts(&output_dir),
+            [
+                (
+                    "type=PushEvent/date=2024-01-01/hour=15/part-0000.jsonl".to_string(),
+                    2
+                ),
+                (
+                    "type=PushEvent/date=2024-01-01/hour=16/part-0000.jsonl".to_string(),
+                    1
+                ),
+                (
+                    "type=WatchEvent/date=2024-01-02/hour=03/part-0000.jsonl".to_string(),
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn partitions_by_org_and_repo_owner_with_defaults_and_escaping() {
+        let tmp_dir = tempdir().unwrap();
+        write_input(tmp_dir.path());
+        let output_dir = tmp_dir.path().join("out");
+
+        let keys = [PartitionKey::RepoOwner, PartitionKey::Org];
+        run(config(tmp_dir.path(), &output_dir, &keys)).unwrap();
+
+        assert_eq!(
+            parts(&output_dir),
+            [
+                (
+                    "repo_owner=rust-lang/org=__HIVE_DEFAULT_PARTITION__/part-0000.jsonl"
+                        .to_string(),
+                    2
+                ),
+                (
+                    "repo_owner=rust-lang/org=my%20org%2Fx/part-0000.jsonl".to_string(),
+                    1
+                ),
+                (
+                    "repo_owner=rust-lang/org=rust-lang/part-0000.jsonl".to_string(),
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rotates_parts_by_event_count() {
+        let tmp_dir = tempdir().unwrap();
+        write_input(tmp_dir.path());
+        let output_dir = tmp_dir.path().join("out");
+
+        let config = Config {
+            max_part_events: Some(2),
+            ..config(tmp_dir.path(), &output_dir, &[PartitionKey::Type])
+        };
+        run(config).unwrap();
+
+        assert_eq!(
+            parts(&output_dir),
+            [
+                ("type=PushEvent/part-0000.jsonl".to_string(), 2),
+                ("type=PushEvent/part-0001.jsonl".to_string(), 1),
+                ("type=WatchEvent/part-0000.jsonl".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn rotates_parts_by_size() {
+        let tmp_dir = tempdir().unwrap();
+        write_input(tmp_dir.path());
+        let output_dir = tmp_dir.path().join("out");
+
+        // Any single event is larger than this, so every part holds exactly one event.
+        let config = Config {
+            max_part_bytes: Some(10),
+            ..config(tmp_dir.path(), &output_dir, &[PartitionKey::Date])
+        };
+        run(config).unwrap();
+
+        let parts = parts(&output_dir);
+        assert_eq!(parts.len(), 4);
+        assert!(parts.iter().all(|(_, lines)| *lines == 1));
+        assert_eq!(parts[2].0, "date=2024-01-01/part-0002.jsonl");
+    }
+
+    #[test]
+    fn rerun_replaces_partition_contents() {
+        let tmp_dir = tempdir().unwrap();
+        write_input(tmp_dir.path());
+        let output_dir = tmp_dir.path().join("out");
+
+        let rotated = Config {
+            max_part_events: Some(1),
+            ..config(tmp_dir.path(), &output_dir, &[PartitionKey::Type])
+        };
+        run(rotated).unwrap();
+        run(config(tmp_dir.path(), &output_dir, &[PartitionKey::Type])).unwrap();
+
+        assert_eq!(
+            parts(&output_dir),
+            [
+                ("type=PushEvent/part-0000.jsonl".to_string(), 3),
+                ("type=WatchEvent/part-0000.jsonl".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn more_partitions_than_open_parts() {
+        let tmp_dir = tempdir().unwrap();
+        let orgs = MAX_OPEN_PARTS as u32 + 10;
+        // The first org comes back once its part was closed to make room for the others.
+        let content: Vec<String> = (0..orgs)
+            .chain([0])
+            .enumerate()
+            .map(|(id, org)| {
+                let org = format!("org{}", org);
+                event(id as u32, "PushEvent", "2024-01-01T15:00:00Z", Some(&org))
+            })
+            .collect();
+        fs::write(tmp_dir.path().join("events.json"), content.join("\n")).unwrap();
+        let output_dir = tmp_dir.path().join("out");
+
+        run(config(tmp_dir.path(), &output_dir, &[PartitionKey::Org])).unwrap();
+
+        let parts = parts(&output_dir);
+        assert_eq!(parts.len(), orgs as usize + 1);
+        assert!(parts.iter().all(|(_, lines)| *lines == 1));
+        assert!(parts.contains(&("org=org0/part-0000.jsonl".to_string(), 1)));
+        assert!(parts.contains(&("org=org0/part-0001.jsonl".to_string(), 1)));
+    }
+
+    #[test]
+    fn partition_keys_parse_and_reject_unknown_names() {
+        assert_eq!(
+            "repo-owner".parse::<PartitionKey>().unwrap(),
+            PartitionKey::RepoOwner
+        );
+        assert_eq!("HOUR".parse::<PartitionKey>().unwrap(), PartitionKey::Hour);
+        assert!(matches!(
+            "minute".parse::<PartitionKey>(),
+            Err(EtlError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn partitioning_requires_jsonl() {
+        let tmp_dir = tempdir().unwrap();
+        let config = Config {
+            output_format: OutputFormat::Csv,
+            ..config(
+                tmp_dir.path(),
+                &tmp_dir.path().join("out"),
+                &[PartitionKey::Type],
+            )
+        };
+        let err = run(config).unwrap_err();
+        assert!(err.to_string().contains("Partitioned output"));
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use etl_rust::extract::source::{InputSource, read_manifest};
+    use etl_rust::{Compression, Config, EtlError, run};
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+    use tempfile::tempdir;
+
+    fn event(id: u32) -> String {
+        format!(
+            r#"{{"id":"{id}","type":"PushEvent","actor":{{"id":1,"login":"a","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{}},"public":true,"created_at":"2024-01-01T00:00:00Z"}}"#
+        )
+    }
+
+    fn config(path: &Path) -> Config {
+        Config {
+            path_to_data: path.to_str().unwrap().to_string(),
+            quiet_mode: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_file_is_read_whatever_its_name() {
+        let tmp_dir = tempdir().unwrap();
+        let file = tmp_dir.path().join("events.txt");
+        fs::write(&file, format!("{}\n{}", event(1), event(2))).unwrap();
+
+        let summary = run(config(&file)).unwrap();
+
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.events, 2);
+    }
+
+    #[test]
+    fn file_list_mixes_files_and_folders() {
+        let tmp_dir = tempdir().unwrap();
+        let folder = tmp_dir.path().join("folder");
+        fs::create_dir(&folder).unwrap();
+        fs::write(folder.join("2024-01-01-0.json"), event(1)).unwrap();
+        let file = tmp_dir.path().join("extra.json");
+        fs::write(&file, event(2)).unwrap();
+
+        let summary = run(Config {
+            extra_paths: vec![file.to_str().unwrap().to_string()],
+            ..config(&folder)
+        })
+        .unwrap();
+
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.events, 2);
+    }
+
+    #[test]
+    fn manifest_lists_inputs_relative_to_itself() {
+        let tmp_dir = tempdir().unwrap();
+        fs::write(tmp_dir.path().join("a.json"), event(1)).unwrap();
+        fs::create_dir(tmp_dir.path().join("more")).unwrap();
+        fs::write(tmp_dir.path().join("more/b.json"), event(2)).unwrap();
+        let manifest = tmp_dir.path().join("inputs.txt");
+        fs::write(&manifest, "# inputs\na.json\n\n  more  \n").unwrap();
+
+        assert_eq!(
+            read_manifest(&manifest).unwrap(),
+            [tmp_dir.path().join("a.json"), tmp_dir.path().join("more")]
+        );
+
+        let summary = run(Config {
+            manifest: Some(manifest.to_str().unwrap().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.events, 2);
+    }
+
+    #[test]
+    fn missing_manifest_is_an_io_error() {
+        let tmp_dir = tempdir().unwrap();
+        let err = run(Config {
+            manifest: Some(tmp_dir.path().join("missing.txt").display().to_string()),
+            quiet_mode: true,
+            ..Default::default()
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Io { .. }));
+    }
+
+    #[test]
+    fn dash_selects_stdin() {
+        let source = InputSource::from_config(&config(Path::new("-")), None, None).unwrap();
+
+        assert!(matches!(source, InputSource::Stdin));
+        assert_eq!(source.list().unwrap(), [Path::new("-")]);
+    }
+
+    #[test]
+    fn stdin_cannot_be_mixed_with_other_inputs() {
+        let err = InputSource::from_config(
+            &Config {
+                extra_paths: vec!["data".to_string()],
+                ..config(Path::new("-"))
+            },
+            None,
+            None,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    #[test]
+    fn stdin_cannot_be_checkpointed() {
+        let tmp_dir = tempdir().unwrap();
+        let err = run(Config {
+            checkpoint_file: Some(tmp_dir.path().join("ckpt.json").display().to_string()),
+            ..config(Path::new("-"))
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    #[test]
+    fn no_input_is_a_config_error() {
+        let err = run(Config {
+            quiet_mode: true,
+            ..Default::default()
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+
+    #[test]
+    fn compression_is_recognized_by_magic_bytes() {
+        assert_eq!(Compression::from_magic(&[0x1f, 0x8b, 8]), Compression::Gzip);
+        assert_eq!(
+            Compression::from_magic(&[0x28, 0xb5, 0x2f, 0xfd]),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::from_magic(b"BZh91AY"), Compression::Bzip2);
+        assert_eq!(Compression::from_magic(b"{\"id\""), Compression::None);
+    }
+
+    #[test]
+    fn cli_reads_gzipped_stdin() {
+        let tmp_dir = tempdir().unwrap();
+        let output = tmp_dir.path().join("out.jsonl");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        write!(encoder, "{}\n{}\n", event(1), event(2)).unwrap();
+        let input = encoder.finish().unwrap();
+
+        let mut child = Command::new(env!("CARGO_BIN_EXE_etl_rust"))
+            .args(["-", "--quiet", "--output", output.to_str().unwrap()])
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(&input).unwrap();
+        let status = child.wait().unwrap();
+
+        assert!(status.success());
+        assert_eq!(fs::read_to_string(&output).unwrap().lines().count(), 2);
+    }
+}
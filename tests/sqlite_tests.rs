@@ -0,0 +1,120 @@
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use etl_rust::{Config, OutputFormat, run};
+    use rusqlite::Connection;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn event(id: u32, actor: u32, login: &str, org: bool) -> String {
+        let org = if org {
+            r#","org":{"id":7,"login":"acme","gravatar_id":"","url":"","avatar_url":""}"#
+        } else {
+            ""
+        };
+        format!(
+            r#"{{"id":"{id}","type":"PushEvent","actor":{{"id":{actor},"login":"{login}","gravatar_id":"","url":"","avatar_url":""}},"repo":{{"id":1,"name":"owner/repo","url":""}},"payload":{{"ref":"refs/heads/main","push_id":{id}}},"public":true,"created_at":"2024-01-01T10:00:00Z"{org}}}"#
+        )
+    }
+
+    fn load(input_dir: &Path, database: &Path, streaming: bool) {
+        let config = Config {
+            path_to_data: input_dir.to_str().unwrap().to_string(),
+            output_file: Some(database.to_str().unwrap().to_string()),
+            output_format: OutputFormat::Sqlite,
+            streaming,
+            quiet_mode: true,
+            ..Default::default()
+        };
+        run(config).unwrap();
+    }
+
+    fn count(connection: &Connection, table: &str) -> i64 {
+        connection
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    fn load_normalized(streaming: bool) {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let content = [
+            event(1, 10, "alice", true),
+            event(2, 10, "alice", false),
+            event(3, 11, "bob", true),
+        ]
+        .join("\n");
+        fs::write(input_dir.join("2024-01-01-10.json"), content).unwrap();
+
+        let database = tmp_dir.path().join("events.db");
+        load(&input_dir, &database, streaming);
+        // Loading the same hour again must not duplicate anything.
+        load(&input_dir, &database, streaming);
+
+        let connection = Connection::open(&database).unwrap();
+        assert_eq!(count(&connection, "events"), 3);
+        assert_eq!(count(&connection, "actors"), 2);
+        assert_eq!(count(&connection, "repos"), 1);
+        assert_eq!(count(&connection, "orgs"), 1);
+
+        let (org_id, login, push_id): (Option<i64>, String, i64) = connection
+            .query_row(
+                "SELECT e.org_id, a.login, json_extract(e.payload, '$.push_id')
+                 FROM events e JOIN actors a ON a.id = e.actor_id WHERE e.id = '3'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(org_id, Some(7));
+        assert_eq!(login, "bob");
+        assert_eq!(push_id, 3);
+
+        let created_at: String = connection
+            .query_row("SELECT created_at FROM events WHERE id = '2'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(created_at, "2024-01-01T10:00:00Z");
+    }
+
+    #[test]
+    fn sqlite_load_is_normalized_and_idempotent() {
+        load_normalized(false);
+    }
+
+    #[test]
+    fn sqlite_streaming_load_is_normalized_and_idempotent() {
+        load_normalized(true);
+    }
+
+    #[test]
+    fn sqlite_load_adds_to_existing_database() {
+        let tmp_dir = tempdir().unwrap();
+        let input_dir = tmp_dir.path().join("input");
+        fs::create_dir(&input_dir).unwrap();
+        let database = tmp_dir.path().join("events.db");
+
+        fs::write(input_dir.join("a.json"), event(1, 10, "alice", false)).unwrap();
+        load(&input_dir, &database, false);
+
+        fs::remove_file(input_dir.join("a.json")).unwrap();
+        fs::write(
+            input_dir.join("b.json"),
+            event(2, 10, "alice-renamed", false),
+        )
+        .unwrap();
+        load(&input_dir, &database, false);
+
+        let connection = Connection::open(&database).unwrap();
+        assert_eq!(count(&connection, "events"), 2);
+        let login: String = connection
+            .query_row("SELECT login FROM actors WHERE id = 10", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(login, "alice-renamed");
+    }
+}
@@ -0,0 +1,166 @@
+#[path = "common/mod.rs"]
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::{Setup, event};
+    use etl_rust::{Config, EtlError, OutputFormat, RunSummary, run_until};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread::{self, JoinHandle};
+    use std::time::{Duration, Instant};
+
+    fn config(s: &Setup) -> Config {
+        Config {
+            watch: true,
+            watch_settle: Duration::from_millis(50),
+            ..s.config()
+        }
+    }
+
+    struct Watching {
+        stop: Arc<AtomicBool>,
+        handle: JoinHandle<Result<RunSummary, EtlError>>,
+    }
+
+    impl Watching {
+        fn start(config: Config) -> Self {
+            let stop = Arc::new(AtomicBool::new(false));
+            let flag = Arc::clone(&stop);
+            let handle = thread::spawn(move || run_until(config, &flag));
+            Watching { stop, handle }
+        }
+
+        fn stop(self) -> Result<RunSummary, EtlError> {
+            self.stop.store(true, Ordering::SeqCst);
+            self.handle.join().unwrap()
+        }
+    }
+
+    fn wait_for(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !condition() {
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for the watcher"
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn processes_existing_and_new_files() {
+        let s = Setup::new("out.jsonl");
+        s.write("2024-01-01-0.json", &[1, 2]);
+        let watching = Watching::start(config(&s));
+
+        wait_for(|| s.output_lines() == 2);
+        s.write("2024-01-01-1.json", &[3]);
+        wait_for(|| s.output_lines() == 3);
+        let summary = watching.stop().unwrap();
+
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.events, 3);
+    }
+
+    #[test]
+    fn stop_finishes_outputs() {
+        let s = Setup::new("out.jsonl");
+        let reject_file = s.output.with_file_name("rejected.jsonl");
+        let watching = Watching::start(Config {
+            reject_file: Some(reject_file.to_str().unwrap().to_string()),
+            ..config(&s)
+        });
+        fs::write(s.input.join("2024-01-01-0.json"), "not json\n").unwrap();
+
+        wait_for(|| fs::read_to_string(&reject_file).is_ok_and(|c| !c.is_empty()));
+        let summary = watching.stop().unwrap();
+
+        assert_eq!(summary.rejected, 1);
+        assert!(s.output.exists());
+    }
+
+    #[test]
+    fn file_that_has_not_settled_is_not_processed() {
+        let s = Setup::new("out.jsonl");
+        let watching = Watching::start(Config {
+            watch_settle: Duration::from_secs(3600),
+            ..config(&s)
+        });
+        s.write("2024-01-01-0.json", &[1]);
+        thread::sleep(Duration::from_millis(300));
+
+        let summary = watching.stop().unwrap();
+
+        assert_eq!(summary.files, 0);
+        assert_eq!(s.output_lines(), 0);
+    }
+
+    #[test]
+    fn without_state_the_output_starts_over() {
+        let s = Setup::new("out.jsonl");
+        fs::write(&s.output, format!("{}\n", event(0))).unwrap();
+        s.write("2024-01-01-0.json", &[1]);
+        let watching = Watching::start(config(&s));
+
+        wait_for(|| {
+            s.output_lines() == 1 && fs::read_to_string(&s.output).unwrap().contains("\"1\"")
+        });
+
+        watching.stop().unwrap();
+        assert_eq!(s.output_lines(), 1);
+    }
+
+    #[test]
+    fn incremental_watch_continues_after_restart() {
+        let s = Setup::new("out.jsonl");
+        let incremental = || Config {
+            incremental_state: Some(s.state.to_str().unwrap().to_string()),
+            ..config(&s)
+        };
+        s.write("2024-01-01-0.json", &[1]);
+        let watching = Watching::start(incremental());
+        wait_for(|| s.output_lines() == 1);
+        watching.stop().unwrap();
+
+        s.write("2024-01-01-1.json", &[2]);
+        let watching = Watching::start(incremental());
+        wait_for(|| s.output_lines() == 2);
+        let summary = watching.stop().unwrap();
+
+        assert_eq!(summary.files, 1);
+        assert_eq!(summary.skipped_files, 1);
+    }
+
+    #[test]
+    fn missing_folder_is_an_error() {
+        let s = Setup::new("out.jsonl");
+        let err = run_until(
+            Config {
+                path_to_data: Path::new(&s.input).join("missing").display().to_string(),
+                ..config(&s)
+            },
+            &AtomicBool::new(false),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Io { .. }));
+    }
+
+    #[test]
+    fn parquet_output_is_rejected() {
+        let s = Setup::new("out.jsonl");
+        let err = run_until(
+            Config {
+                output_format: OutputFormat::Parquet,
+                ..config(&s)
+            },
+            &AtomicBool::new(false),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, EtlError::Config(_)));
+    }
+}